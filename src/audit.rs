@@ -0,0 +1,824 @@
+use crate::baseline::{self, BaselineEntry};
+use crate::cache::{self, CacheProvider};
+use crate::config::Config;
+use crate::git::{self, CrashTestFile, DeletedCrashTest};
+use crate::github::{GitHubProvider, GitHubRepo, IssueLookup};
+use crate::issues::{FileProvider, IssueStateProvider, IssueStates};
+use crate::model::{
+    AuditReport, ClosedWithTests, Findings, IssueDeletions, IssueMismatch, PullRequestReference, RunMetadata,
+};
+use crate::pr::{PrExtractor, PrRecognizer};
+use crate::progress::Progress;
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Where issue states come from
+#[derive(Clone)]
+pub enum OpenIssueSource {
+    /// The GitHub API, through the local cache unless `refresh` is set
+    GitHub { refresh: bool },
+    /// The local cache only, failing if there is none
+    Offline,
+    /// A JSON or CSV file of issue states (see [`FileProvider`])
+    File(PathBuf),
+    /// Any other provider
+    Custom(Arc<dyn IssueStateProvider>),
+}
+
+/// Outcome of an audit run
+#[derive(Debug, Clone)]
+pub struct AuditResult {
+    pub report: AuditReport,
+    /// Deleted crash tests found in the date range, newest first
+    pub deleted_files: Vec<DeletedCrashTest>,
+    /// Baseline entries past their expiry date, which no longer suppress anything
+    pub expired_baseline_entries: Vec<BaselineEntry>,
+    /// Number of entries added to the baseline file, if it was updated
+    pub baseline_entries_added: Option<usize>,
+}
+
+/// Configures and runs an audit of one repository
+///
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use rust_crash_audit::Auditor;
+///
+/// let result = Auditor::new("path/to/rust")
+///     .date_range(Some("2024-01-01".parse()?), None)
+///     .check_existing(true)
+///     .run()
+///     .await?;
+/// println!("{} issues out of sync", result.report.out_of_sync.len());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Auditor {
+    repo_path: PathBuf,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    owner: Option<String>,
+    name: Option<String>,
+    github_token: Option<String>,
+    config: Config,
+    pr_formats: Option<Vec<PrRecognizer>>,
+    issue_source: OpenIssueSource,
+    issue_lookup: IssueLookup,
+    full_refresh: bool,
+    max_cache_age: Option<Duration>,
+    cache_dir: PathBuf,
+    jobs: Option<usize>,
+    rescan: bool,
+    check_existing: bool,
+    baseline: Option<PathBuf>,
+    update_baseline: bool,
+    progress: Progress,
+    verbose: bool,
+}
+
+impl Auditor {
+    /// Audit the repository at `repo_path` with the default settings
+    pub fn new(repo_path: impl Into<PathBuf>) -> Self {
+        Self {
+            repo_path: repo_path.into(),
+            from: None,
+            to: None,
+            owner: None,
+            name: None,
+            github_token: None,
+            config: Config::default(),
+            pr_formats: None,
+            issue_source: OpenIssueSource::GitHub { refresh: false },
+            issue_lookup: IssueLookup::default(),
+            full_refresh: false,
+            max_cache_age: None,
+            cache_dir: cache::default_cache_dir(),
+            jobs: None,
+            rescan: false,
+            check_existing: false,
+            baseline: None,
+            update_baseline: false,
+            progress: Progress::default(),
+            verbose: false,
+        }
+    }
+
+    /// Only consider deletions between `from` and `to` (inclusive)
+    pub fn date_range(mut self, from: Option<NaiveDate>, to: Option<NaiveDate>) -> Self {
+        self.from = from;
+        self.to = to;
+        self
+    }
+
+    /// GitHub repository the issues live in, parts left out are inferred from the `origin` remote
+    pub fn github_repo(mut self, owner: Option<String>, name: Option<String>) -> Self {
+        self.owner = owner;
+        self.name = name;
+        self
+    }
+
+    pub fn github_token(mut self, token: Option<String>) -> Self {
+        self.github_token = token;
+        self
+    }
+
+    /// Watched directories and PR formats, usually from `.crash-audit.toml`
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Commit message formats to recognize PR numbers from, overriding the config
+    pub fn pr_formats(mut self, formats: Option<Vec<PrRecognizer>>) -> Self {
+        self.pr_formats = formats;
+        self
+    }
+
+    /// Where issue states come from (default: GitHub through the cache)
+    pub fn issue_source(mut self, source: OpenIssueSource) -> Self {
+        self.issue_source = source;
+        self
+    }
+
+    /// How to look up issue states on GitHub when the cache is not used
+    pub fn issue_lookup(mut self, lookup: IssueLookup) -> Self {
+        self.issue_lookup = lookup;
+        self
+    }
+
+    /// Refresh the issue cache automatically once it is older than this
+    /// (default: `max_cache_age` from the config, or never)
+    pub fn max_cache_age(mut self, max_age: Option<Duration>) -> Self {
+        self.max_cache_age = max_age;
+        self
+    }
+
+    /// Directory for the issue cache and scan checkpoints
+    /// (default: `$XDG_CACHE_HOME/rust-crash-audit`)
+    pub fn cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = cache_dir.into();
+        self
+    }
+
+    /// Refresh the cache by listing every open issue again rather than
+    /// fetching only the changes since it was written
+    pub fn full_refresh(mut self, full_refresh: bool) -> Self {
+        self.full_refresh = full_refresh;
+        self
+    }
+
+    /// Number of worker threads for the history scan (default: available CPUs)
+    pub fn jobs(mut self, jobs: Option<usize>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Ignore the saved scan checkpoint and walk the full history again
+    pub fn rescan(mut self, rescan: bool) -> Self {
+        self.rescan = rescan;
+        self
+    }
+
+    /// Also look for crash tests still present whose issue is already closed
+    pub fn check_existing(mut self, check_existing: bool) -> Self {
+        self.check_existing = check_existing;
+        self
+    }
+
+    /// Suppress the findings listed in a baseline file, optionally adding the current ones first
+    pub fn baseline(mut self, path: impl Into<PathBuf>, update: bool) -> Self {
+        self.baseline = Some(path.into());
+        self.update_baseline = update;
+        self
+    }
+
+    /// Where progress messages and warnings go (default: nowhere)
+    pub fn progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Also report per-issue progress details
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Scan the history, look up issue states and build the report
+    pub async fn run(&self) -> Result<AuditResult> {
+        let repo_path = &self.repo_path;
+
+        // Validate repository path
+        if !repo_path.exists() {
+            anyhow::bail!("Repository path does not exist: {:?}", repo_path);
+        }
+
+        if !repo_path.is_dir() {
+            anyhow::bail!("Repository path is not a directory: {:?}", repo_path);
+        }
+
+        // Validate date range
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                anyhow::bail!("Start date must be before end date");
+            }
+        }
+
+        if self.check_existing && matches!(self.issue_source, OpenIssueSource::Offline) {
+            anyhow::bail!("Checking existing crash tests needs GitHub for close details and cannot run offline");
+        }
+
+        let progress = self.effective_progress();
+        let github_repo = resolve_github_repo(repo_path, self.owner.clone(), self.name.clone(), &progress)?;
+
+        progress.message(format!("Scanning {} repository...", github_repo));
+        if let Some(from) = self.from {
+            let to = self.to.map(|to| to.to_string()).unwrap_or_else(|| "present".to_string());
+            progress.message(format!("Date range: {} to {}", from, to));
+        }
+        progress.message("");
+
+        // Scan git history for deleted crash test files
+        let pr_extractor = match self.pr_formats.clone().or_else(|| self.config.pr_formats.clone()) {
+            Some(formats) => PrExtractor::new(formats),
+            None => PrExtractor::default(),
+        };
+
+        // Resume from the last scan checkpoint unless a full rescan was requested
+        let checkpoint = if self.rescan {
+            None
+        } else {
+            cache::load_scan_checkpoint(&self.cache_dir, repo_path, &git::scan_key(&self.config.watch))
+                .unwrap_or_else(|e| {
+                    progress.message(format!("Ignoring unreadable scan checkpoint: {:#}", e));
+                    None
+                })
+        };
+
+        let jobs = self.jobs.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        });
+
+        let scan_options = git::ScanOptions {
+            groups: &self.config.watch,
+            pr_extractor: &pr_extractor,
+            github_repo: &github_repo,
+            jobs,
+            progress: &progress,
+        };
+
+        let checkpoint = git::scan_deleted_crash_tests(repo_path, checkpoint, self.from, &scan_options)
+            .context("Failed to scan git history")?;
+
+        let deleted_files = git::filter_by_date(checkpoint.deleted_files.clone(), self.from, self.to);
+
+        let head_sha = checkpoint.head.clone();
+        cache::save_scan_checkpoint(&self.cache_dir, checkpoint)
+            .context("Failed to save scan checkpoint")?;
+
+        progress.message(format!("Found {} deleted crash test files\n", deleted_files.len()));
+
+        if !deleted_files.is_empty() {
+            report_format_counts(&deleted_files, &progress);
+        }
+
+        // Get current crash test files to detect partial deletions
+        let current_files = git::get_current_crash_test_files(repo_path, &self.config.watch, &github_repo)
+            .context("Failed to scan current crash test files")?;
+
+        progress.detail("Current crash test files:");
+        for group in &self.config.watch {
+            let count = current_files.iter().filter(|f| f.group == group.name).count();
+            progress.detail(format!("  {} ({}): {}", group.name, group.pathspec(), count));
+        }
+        progress.detail("");
+
+        // Look up every issue referenced by a deleted or current crash test
+        let numbers: Vec<u64> = deleted_files
+            .iter()
+            .flat_map(|file| git::issue_numbers(&file.issues))
+            .chain(current_files.iter().flat_map(|file| git::issue_numbers(&file.issues)))
+            .collect::<BTreeSet<u64>>()
+            .into_iter()
+            .collect();
+        let issue_states = self.fetch_issue_states(&github_repo, &numbers).await?;
+
+        let unknown = numbers
+            .iter()
+            .filter(|number| {
+                !issue_states.states.contains_key(number) && !issue_states.pull_requests.contains(number)
+            })
+            .count();
+        if unknown > 0 {
+            progress.message(format!("Warning: no state known for {} issue(s), treating them as closed\n", unknown));
+        }
+
+        progress.message("Checking deleted files against open issues...");
+        let mut findings = categorize(&deleted_files, &current_files, &issue_states);
+        report_categories(&findings, &progress);
+        progress.message("");
+
+        // Inverse audit: crash tests still present whose issue is already closed
+        if self.check_existing {
+            let closed = self
+                .closed_with_tests(&github_repo, &current_files, &issue_states, &pr_extractor)
+                .await?;
+            findings.closed_with_tests = Some(closed);
+        }
+
+        // Hide findings that are already known
+        let mut expired_baseline_entries = Vec::new();
+        let mut baseline_entries_added = None;
+        if let Some(path) = &self.baseline {
+            let mut suppressions = baseline::load_baseline(path)?;
+            let today = Utc::now().date_naive();
+            expired_baseline_entries = suppressions.expired(today).cloned().collect();
+            if self.update_baseline {
+                baseline_entries_added = Some(suppressions.add_findings(&findings, today));
+                baseline::save_baseline(path, &suppressions)?;
+            }
+            suppressions.apply(&mut findings, today);
+        }
+
+        let cache_age = issue_states
+            .cached
+            .then(|| Duration::from_secs((Utc::now() - issue_states.fetched_at).num_seconds().max(0) as u64));
+        let max_cache_age = self.effective_max_cache_age();
+        let cache_stale = self.uses_issue_cache()
+            && matches!((cache_age, max_cache_age), (Some(age), Some(max_age)) if age > max_age);
+
+        let report = AuditReport::new(
+            RunMetadata {
+                tool_version: env!("CARGO_PKG_VERSION").to_string(),
+                generated_at: Utc::now(),
+                repository: github_repo,
+                head_sha,
+                from: self.from,
+                to: self.to,
+                issues_fetched_at: issue_states.fetched_at,
+                cache_age_seconds: cache_age.map(|age| age.as_secs()),
+                cache_max_age_seconds: max_cache_age.map(|max_age| max_age.as_secs()),
+                cache_stale,
+            },
+            findings,
+            issue_states.open().len(),
+        );
+
+        Ok(AuditResult {
+            report,
+            deleted_files,
+            expired_baseline_entries,
+            baseline_entries_added,
+        })
+    }
+
+    fn effective_progress(&self) -> Progress {
+        self.progress.clone().verbose(self.verbose)
+    }
+
+    fn effective_max_cache_age(&self) -> Option<Duration> {
+        self.max_cache_age.or(self.config.max_cache_age)
+    }
+
+    /// Whether issue states come from the local cache rather than a file or custom provider
+    fn uses_issue_cache(&self) -> bool {
+        matches!(self.issue_source, OpenIssueSource::GitHub { .. } | OpenIssueSource::Offline)
+    }
+
+    /// Issue states from the configured source, refreshing a cache older than
+    /// the maximum age and falling back to it if GitHub can't be reached
+    async fn fetch_issue_states(&self, github_repo: &GitHubRepo, numbers: &[u64]) -> Result<IssueStates> {
+        let stale_age = match (self.effective_max_cache_age(), self.uses_issue_cache()) {
+            (Some(max_age), true) if cache::cache_exists(&self.cache_dir, github_repo) => {
+                cache::load_cache(&self.cache_dir, github_repo, &self.effective_progress())
+                    .ok()
+                    .map(|cached| cached.age())
+                    .filter(|age| *age > max_age)
+                    .map(|age| (age, max_age))
+            }
+            _ => None,
+        };
+
+        let progress = self.effective_progress();
+        match (&self.issue_source, stale_age) {
+            (OpenIssueSource::GitHub { refresh: false }, Some((age, max_age))) => {
+                progress.message(format!(
+                    "Cache is {} old (maximum {}), refreshing...\n",
+                    cache::format_duration(age),
+                    cache::format_duration(max_age)
+                ));
+                // Batched lookups don't write the cache, so list even when
+                // --issue-lookup would pick them
+                let provider = self.github_provider(github_repo).lookup(IssueLookup::List);
+                match provider.issue_states(numbers).await {
+                    Ok(issue_states) => return Ok(issue_states),
+                    Err(e) => {
+                        // octocrab errors append a backtrace, keep the warning to the message
+                        let message = format!("{:#}", e);
+                        let message = message.lines().next().unwrap_or_default();
+                        progress.message(format!("⚠️  WARNING: failed to refresh the stale issue cache: {}", message));
+                        progress.message(format!(
+                            "⚠️  WARNING: using cached issue states from {} ago instead, results may be out of date\n",
+                            cache::format_duration(age)
+                        ));
+                    }
+                }
+            }
+            (OpenIssueSource::Offline, Some((age, max_age))) => {
+                progress.message(format!(
+                    "⚠️  WARNING: the issue cache is {} old (maximum {}), results may be out of date\n",
+                    cache::format_duration(age),
+                    cache::format_duration(max_age)
+                ));
+            }
+            _ => {}
+        }
+
+        self.issue_provider(github_repo).issue_states(numbers).await
+    }
+
+    fn github_provider(&self, github_repo: &GitHubRepo) -> GitHubProvider {
+        GitHubProvider::new(github_repo.clone(), self.github_token.clone())
+            .progress(self.effective_progress())
+            .lookup(self.issue_lookup)
+            .full_refresh(self.full_refresh)
+            .cache_dir(&self.cache_dir)
+    }
+
+    /// Provider for the configured issue source
+    fn issue_provider(&self, github_repo: &GitHubRepo) -> Arc<dyn IssueStateProvider> {
+        match &self.issue_source {
+            OpenIssueSource::GitHub { refresh: false } if cache::cache_exists(&self.cache_dir, github_repo) => {
+                Arc::new(CacheProvider::new(&self.cache_dir, github_repo.clone()).progress(self.effective_progress()))
+            }
+            OpenIssueSource::GitHub { refresh } => {
+                if *refresh {
+                    self.effective_progress().detail("Refreshing cache...\n");
+                }
+                Arc::new(self.github_provider(github_repo))
+            }
+            OpenIssueSource::Offline => {
+                Arc::new(CacheProvider::new(&self.cache_dir, github_repo.clone()).progress(self.effective_progress()))
+            }
+            OpenIssueSource::File(path) => Arc::new(FileProvider::new(path).progress(self.effective_progress())),
+            OpenIssueSource::Custom(provider) => provider.clone(),
+        }
+    }
+
+    /// Confirm which candidate issues are closed and find the PR that closed them
+    async fn closed_with_tests(
+        &self,
+        github_repo: &GitHubRepo,
+        current_files: &[CrashTestFile],
+        issue_states: &IssueStates,
+        pr_extractor: &PrExtractor,
+    ) -> Result<Vec<ClosedWithTests>> {
+        let mut candidates = closed_candidates(current_files, issue_states);
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let numbers: Vec<u64> = candidates.keys().copied().collect();
+        let closed_issues = self
+            .github_provider(github_repo)
+            .closed_issue_details(&numbers, issue_states)
+            .await
+            .context("Failed to fetch closed issue details from GitHub")?;
+
+        let mut closed_with_tests = Vec::new();
+        for closed in closed_issues {
+            // Prefer the PR from the closing commit in local history, fall
+            // back to the last PR that referenced the issue
+            let closing_pr = match &closed.closing_commit {
+                Some(sha) => git::find_pr_for_commit(&self.repo_path, sha, pr_extractor)
+                    .context("Failed to look up closing commit")?,
+                None => None,
+            }
+            .or(closed.referencing_pr);
+
+            closed_with_tests.push(ClosedWithTests {
+                issue_number: closed.number,
+                files: candidates.remove(&closed.number).unwrap_or_default(),
+                closed_at: closed.closed_at,
+                closing_pr,
+            });
+        }
+
+        Ok(closed_with_tests)
+    }
+}
+
+/// Sort deleted crash tests into findings by whether their issue is open and
+/// whether other tests for it remain
+/// `closed_with_tests` is left unset since it needs issue details from GitHub.
+pub fn categorize(
+    deleted_files: &[DeletedCrashTest],
+    current_files: &[CrashTestFile],
+    issue_states: &IssueStates,
+) -> Findings {
+    let open_issues = issue_states.open();
+
+    // Group deleted files by issue number, pull requests are reported separately
+    let mut files_by_issue: BTreeMap<u64, Vec<DeletedCrashTest>> = BTreeMap::new();
+    for file in deleted_files {
+        // A test referencing several issues counts towards each of them
+        for issue_number in git::issue_numbers(&file.issues) {
+            if issue_states.pull_requests.contains(&issue_number) {
+                continue;
+            }
+            files_by_issue
+                .entry(issue_number)
+                .or_default()
+                .push(file.clone());
+        }
+    }
+
+    let mut findings = Findings {
+        issue_mismatches: issue_mismatches(deleted_files, current_files),
+        pull_request_references: pull_request_references(deleted_files, current_files, issue_states),
+        ..Findings::default()
+    };
+
+    for (issue_number, files) in files_by_issue {
+        // Count how many files for this issue still exist
+        let remaining_files = current_files
+            .iter()
+            .filter(|file| git::issue_numbers(&file.issues).any(|n| n == issue_number))
+            .count();
+
+        let metadata = issue_states.issues.get(&issue_number);
+        let issue = IssueDeletions {
+            issue_number,
+            issue_open: open_issues.contains(&issue_number),
+            title: metadata.and_then(|issue| issue.title.clone()),
+            state_reason: metadata.and_then(|issue| issue.state_reason),
+            files,
+            remaining_files,
+        };
+
+        if remaining_files > 0 {
+            // Partial deletion - some files remain
+            findings.partially_deleted.push(issue);
+        } else if issue.issue_open {
+            // All files for this issue are gone but it is still open
+            findings.out_of_sync.push(issue);
+        } else {
+            // Issue is closed or doesn't exist - this is expected
+            findings.synced.push(issue);
+        }
+    }
+
+    findings
+}
+
+/// Deleted and current files whose filename and known-bug directives disagree
+fn issue_mismatches(deleted_files: &[DeletedCrashTest], current_files: &[CrashTestFile]) -> Vec<IssueMismatch> {
+    let deleted = deleted_files
+        .iter()
+        .map(|file| (&file.file_path, &file.issues));
+    let current = current_files
+        .iter()
+        .map(|file| (&file.file_path, &file.issues));
+
+    deleted
+        .chain(current)
+        .filter(|(_, issues)| git::has_issue_mismatch(issues))
+        .map(|(file_path, issues)| IssueMismatch {
+            file_path: file_path.clone(),
+            issues: issues.clone(),
+        })
+        .collect()
+}
+
+/// Deleted and current files whose number is a pull request, by pull request
+fn pull_request_references(
+    deleted_files: &[DeletedCrashTest],
+    current_files: &[CrashTestFile],
+    issue_states: &IssueStates,
+) -> Vec<PullRequestReference> {
+    let deleted = deleted_files
+        .iter()
+        .map(|file| (&file.file_path, &file.issues));
+    let current = current_files
+        .iter()
+        .map(|file| (&file.file_path, &file.issues));
+
+    let mut files_by_pr: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+    for (file_path, issues) in deleted.chain(current) {
+        for number in git::issue_numbers(issues) {
+            if issue_states.pull_requests.contains(&number) {
+                files_by_pr.entry(number).or_default().push(file_path.clone());
+            }
+        }
+    }
+
+    files_by_pr
+        .into_iter()
+        .map(|(pr_number, files)| PullRequestReference { pr_number, files })
+        .collect()
+}
+
+/// File names of current crash tests by issue, for issues not known to be open
+pub fn closed_candidates(current_files: &[CrashTestFile], issue_states: &IssueStates) -> BTreeMap<u64, Vec<String>> {
+    let open_issues = issue_states.open();
+
+    let mut present_by_issue: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+    for file in current_files {
+        for issue_number in git::issue_numbers(&file.issues) {
+            if !open_issues.contains(&issue_number) && !issue_states.pull_requests.contains(&issue_number) {
+                present_by_issue
+                    .entry(issue_number)
+                    .or_default()
+                    .push(file.file_name.clone());
+            }
+        }
+    }
+
+    for files in present_by_issue.values_mut() {
+        files.sort();
+    }
+    present_by_issue
+}
+
+/// Determine the GitHub repository to audit
+/// Explicit owner/name win, then the `origin` remote, then rust-lang/rust
+fn resolve_github_repo(
+    repo_path: &Path,
+    owner: Option<String>,
+    name: Option<String>,
+    progress: &Progress,
+) -> Result<GitHubRepo> {
+    let inferred = git::origin_url(repo_path)
+        .context("Failed to read origin remote")?
+        .and_then(|url| GitHubRepo::from_remote_url(&url));
+
+    let default = match inferred {
+        Some(repo) => repo,
+        None => {
+            if owner.is_none() || name.is_none() {
+                progress.message("Could not infer GitHub repository from origin remote, defaulting to rust-lang/rust");
+            }
+            GitHubRepo::default()
+        }
+    };
+
+    Ok(GitHubRepo::new(
+        owner.unwrap_or(default.owner),
+        name.unwrap_or(default.name),
+    ))
+}
+
+/// Show which commit message formats the PR numbers came from, in verbose mode
+fn report_format_counts(deleted_files: &[DeletedCrashTest], progress: &Progress) {
+    if !progress.is_verbose() {
+        return;
+    }
+    let mut format_counts: Vec<(String, usize)> = Vec::new();
+    for file in deleted_files {
+        let name = file
+            .pr_format
+            .map(|format| format.name())
+            .unwrap_or_else(|| "unrecognized".to_string());
+        match format_counts.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, count)) => *count += 1,
+            None => format_counts.push((name, 1)),
+        }
+    }
+    progress.detail("PR references by commit format:");
+    for (name, count) in format_counts {
+        progress.detail(format!("  {}: {}", name, count));
+    }
+    progress.detail("");
+}
+
+fn report_categories(findings: &Findings, progress: &Progress) {
+    for issue in &findings.partially_deleted {
+        progress.detail(format!(
+            "  ℹ️  Issue #{}: {} file(s) deleted, {} remain",
+            issue.issue_number,
+            issue.files.len(),
+            issue.remaining_files
+        ));
+    }
+    for issue in &findings.out_of_sync {
+        progress.detail(format!("  ⚠️  Issue #{} is still OPEN (all files deleted)", issue.issue_number));
+    }
+    for issue in &findings.synced {
+        progress.detail(format!("  ✅ Issue #{} is closed (all files deleted)", issue.issue_number));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::{DeletionKind, IssueRef, IssueSource};
+    use std::collections::HashSet;
+
+    fn issue_refs(numbers: &[(u64, IssueSource)]) -> Vec<IssueRef> {
+        numbers
+            .iter()
+            .map(|&(number, source)| IssueRef { number, source })
+            .collect()
+    }
+
+    fn deleted(path: &str, issues: &[(u64, IssueSource)]) -> DeletedCrashTest {
+        DeletedCrashTest {
+            file_path: path.to_string(),
+            group: "rustc".to_string(),
+            issues: issue_refs(issues),
+            commit_sha: "abc123".to_string(),
+            commit_date: "2024-10-01".to_string(),
+            origin_pr: Some(1),
+            rollup_pr: None,
+            pr_format: None,
+            kind: DeletionKind::Deleted,
+        }
+    }
+
+    fn current(path: &str, issues: &[(u64, IssueSource)]) -> CrashTestFile {
+        CrashTestFile {
+            file_name: path.rsplit('/').next().unwrap().to_string(),
+            file_path: path.to_string(),
+            group: "rustc".to_string(),
+            issues: issue_refs(issues),
+        }
+    }
+
+    fn issue_states(open: &[u64], pull_requests: &[u64]) -> IssueStates {
+        let open: HashSet<u64> = open.iter().copied().collect();
+        let pull_requests: HashSet<u64> = pull_requests.iter().copied().collect();
+        IssueStates::from_open_issues(&open, &pull_requests, &[], Utc::now(), false)
+    }
+
+    fn numbers(issues: &[IssueDeletions]) -> Vec<u64> {
+        issues.iter().map(|issue| issue.issue_number).collect()
+    }
+
+    #[test]
+    fn test_categorize() {
+        use IssueSource::{Both, Directive, Filename};
+
+        let deleted_files = vec![
+            deleted("tests/crashes/100.rs", &[(100, Filename)]),
+            deleted("tests/crashes/200.rs", &[(200, Both)]),
+            deleted("tests/crashes/300.rs", &[(300, Filename)]),
+            // Counts towards both issues
+            deleted("tests/crashes/400.rs", &[(400, Filename), (500, Directive)]),
+            // A pull request, not an issue
+            deleted("tests/crashes/900.rs", &[(900, Filename)]),
+        ];
+        let current_files = vec![
+            current("tests/crashes/300-2.rs", &[(300, Filename)]),
+            current("tests/crashes/600.rs", &[(600, Both)]),
+            current("tests/crashes/900-2.rs", &[(900, Filename)]),
+        ];
+        let issue_states = issue_states(&[100, 300, 500], &[900]);
+
+        let findings = categorize(&deleted_files, &current_files, &issue_states);
+
+        assert_eq!(numbers(&findings.out_of_sync), vec![100, 500]);
+        assert_eq!(numbers(&findings.partially_deleted), vec![300]);
+        assert_eq!(findings.partially_deleted[0].remaining_files, 1);
+        assert!(findings.partially_deleted[0].issue_open);
+        assert_eq!(numbers(&findings.synced), vec![200, 400]);
+        assert!(!findings.synced[0].issue_open);
+        assert_eq!(findings.out_of_sync[1].files[0].file_path, "tests/crashes/400.rs");
+        assert!(findings.closed_with_tests.is_none());
+
+        assert_eq!(
+            findings.issue_mismatches.iter().map(|m| m.file_path.as_str()).collect::<Vec<_>>(),
+            vec!["tests/crashes/400.rs"]
+        );
+        assert_eq!(
+            findings.pull_request_references,
+            vec![PullRequestReference {
+                pr_number: 900,
+                files: vec!["tests/crashes/900.rs".to_string(), "tests/crashes/900-2.rs".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_closed_candidates() {
+        use IssueSource::{Both, Filename};
+
+        let current_files = vec![
+            current("tests/crashes/700-b.rs", &[(700, Filename)]),
+            current("tests/crashes/700-a.rs", &[(700, Filename)]),
+            current("tests/crashes/800.rs", &[(800, Both)]),
+            current("tests/crashes/900.rs", &[(900, Both)]),
+        ];
+
+        let candidates = closed_candidates(&current_files, &issue_states(&[800], &[900]));
+        assert_eq!(
+            candidates.into_iter().collect::<Vec<_>>(),
+            vec![(700, vec!["700-a.rs".to_string(), "700-b.rs".to_string()])]
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use crate::model::{FindingCategory, Findings, IssueDeletions, SuppressedFinding};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Default baseline file, looked up in the current directory
+pub const DEFAULT_BASELINE_FILE: &str = ".crash-audit-baseline.toml";
+
+const HEADER: &str = "\
+# Known findings suppressed by rust-crash-audit.
+# Each entry matches an issue number or a file path. Entries past their
+# `expires` date stop suppressing and produce a warning.
+
+";
+
+/// Findings that are known and should not be reported again
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Baseline {
+    #[serde(default)]
+    pub suppress: Vec<BaselineEntry>,
+}
+
+/// A single suppressed issue or file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BaselineEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issue: Option<u64>,
+    /// File path relative to the repository root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub reason: String,
+    /// Last day the entry applies, as a TOML date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<toml::value::Datetime>,
+}
+
+impl BaselineEntry {
+    /// Expiry date, if one is set
+    pub fn expiry_date(&self) -> Option<NaiveDate> {
+        let date = self.expires.as_ref()?.date?;
+        NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+    }
+
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expiry_date().is_some_and(|expires| expires < today)
+    }
+
+    /// Human-readable description of what the entry matches
+    pub fn describe(&self) -> String {
+        match (&self.issue, &self.path) {
+            (Some(issue), _) => format!("issue #{}", issue),
+            (None, Some(path)) => path.clone(),
+            (None, None) => "empty entry".to_string(),
+        }
+    }
+}
+
+impl Baseline {
+    /// Entries past their expiry date
+    pub fn expired(&self, today: NaiveDate) -> impl Iterator<Item = &BaselineEntry> {
+        self.suppress.iter().filter(move |entry| entry.is_expired(today))
+    }
+
+    /// Move findings matched by unexpired entries into `findings.suppressed`
+    pub fn apply(&self, findings: &mut Findings, today: NaiveDate) {
+        let active: Vec<&BaselineEntry> = self
+            .suppress
+            .iter()
+            .filter(|entry| !entry.is_expired(today))
+            .collect();
+
+        let mut suppressed = Vec::new();
+        for (issues, category) in [
+            (&mut findings.out_of_sync, FindingCategory::OutOfSync),
+            (&mut findings.partially_deleted, FindingCategory::Partial),
+        ] {
+            issues.retain(|issue| {
+                let paths: Vec<&str> = issue.files.iter().map(|f| f.file_path.as_str()).collect();
+                match find_entry(&active, Some(issue.issue_number), &paths) {
+                    Some(entry) => {
+                        suppressed.push(suppressed_finding(entry, category, Some(issue.issue_number), &paths));
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
+
+        if let Some(closed_with_tests) = &mut findings.closed_with_tests {
+            closed_with_tests.retain(|closed| match find_entry(&active, Some(closed.issue_number), &[]) {
+                Some(entry) => {
+                    let files: Vec<&str> = closed.files.iter().map(String::as_str).collect();
+                    suppressed.push(suppressed_finding(
+                        entry,
+                        FindingCategory::Stale,
+                        Some(closed.issue_number),
+                        &files,
+                    ));
+                    false
+                }
+                None => true,
+            });
+        }
+
+        findings.pull_request_references.retain(|reference| {
+            let paths: Vec<&str> = reference.files.iter().map(String::as_str).collect();
+            match find_entry(&active, Some(reference.pr_number), &paths) {
+                Some(entry) => {
+                    suppressed.push(suppressed_finding(
+                        entry,
+                        FindingCategory::PullRequest,
+                        Some(reference.pr_number),
+                        &paths,
+                    ));
+                    false
+                }
+                None => true,
+            }
+        });
+
+        findings.issue_mismatches.retain(|mismatch| {
+            match find_entry(&active, None, &[mismatch.file_path.as_str()]) {
+                Some(entry) => {
+                    suppressed.push(suppressed_finding(
+                        entry,
+                        FindingCategory::Mismatch,
+                        None,
+                        &[mismatch.file_path.as_str()],
+                    ));
+                    false
+                }
+                None => true,
+            }
+        });
+
+        findings.suppressed.extend(suppressed);
+    }
+
+    /// Add entries for findings not covered by any existing entry, expired or not
+    /// Returns the number of entries added.
+    pub fn add_findings(&mut self, findings: &Findings, today: NaiveDate) -> usize {
+        let reason = format!("Added by --update-baseline on {}", today);
+        let all: Vec<&BaselineEntry> = self.suppress.iter().collect();
+        let mut new_entries: Vec<BaselineEntry> = Vec::new();
+
+        let issues = findings
+            .out_of_sync
+            .iter()
+            .chain(&findings.partially_deleted)
+            .map(|issue: &IssueDeletions| {
+                let paths: Vec<&str> = issue.files.iter().map(|f| f.file_path.as_str()).collect();
+                (issue.issue_number, paths)
+            })
+            .chain(
+                findings
+                    .closed_with_tests
+                    .iter()
+                    .flatten()
+                    .map(|closed| (closed.issue_number, Vec::new())),
+            )
+            .chain(findings.pull_request_references.iter().map(|reference| {
+                let paths: Vec<&str> = reference.files.iter().map(String::as_str).collect();
+                (reference.pr_number, paths)
+            }));
+        for (issue_number, paths) in issues {
+            if find_entry(&all, Some(issue_number), &paths).is_none()
+                && !new_entries.iter().any(|e| e.issue == Some(issue_number))
+            {
+                new_entries.push(BaselineEntry {
+                    issue: Some(issue_number),
+                    path: None,
+                    reason: reason.clone(),
+                    expires: None,
+                });
+            }
+        }
+
+        for mismatch in &findings.issue_mismatches {
+            if find_entry(&all, None, &[mismatch.file_path.as_str()]).is_none()
+                && !new_entries.iter().any(|e| e.path.as_ref() == Some(&mismatch.file_path))
+            {
+                new_entries.push(BaselineEntry {
+                    issue: None,
+                    path: Some(mismatch.file_path.clone()),
+                    reason: reason.clone(),
+                    expires: None,
+                });
+            }
+        }
+
+        let added = new_entries.len();
+        self.suppress.extend(new_entries);
+        added
+    }
+}
+
+/// Find the entry suppressing a finding: by issue number, or by covering all its paths
+fn find_entry<'a>(entries: &[&'a BaselineEntry], issue: Option<u64>, paths: &[&str]) -> Option<&'a BaselineEntry> {
+    if let Some(entry) = entries.iter().find(|entry| issue.is_some() && entry.issue == issue) {
+        return Some(entry);
+    }
+
+    let path_entry = |path: &str| {
+        entries
+            .iter()
+            .find(|entry| entry.path.as_deref() == Some(path))
+            .copied()
+    };
+    if paths.is_empty() || !paths.iter().all(|path| path_entry(path).is_some()) {
+        return None;
+    }
+    path_entry(paths[0])
+}
+
+fn suppressed_finding(
+    entry: &BaselineEntry,
+    category: FindingCategory,
+    issue_number: Option<u64>,
+    files: &[&str],
+) -> SuppressedFinding {
+    SuppressedFinding {
+        category,
+        issue_number,
+        files: files.iter().map(|f| f.to_string()).collect(),
+        reason: entry.reason.clone(),
+        expires: entry.expiry_date(),
+    }
+}
+
+/// Load a baseline file, treating a missing file as an empty baseline
+pub fn load_baseline(path: &Path) -> Result<Baseline> {
+    if !path.exists() {
+        return Ok(Baseline::default());
+    }
+
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read baseline file {:?}", path))?;
+
+    parse_baseline(&contents)
+        .with_context(|| format!("Failed to parse baseline file {:?}", path))
+}
+
+/// Write the baseline file
+pub fn save_baseline(path: &Path, baseline: &Baseline) -> Result<()> {
+    let contents = toml::to_string_pretty(baseline)
+        .context("Failed to serialize baseline")?;
+
+    fs::write(path, format!("{}{}", HEADER, contents))
+        .with_context(|| format!("Failed to write baseline file {:?}", path))
+}
+
+/// Parse and validate a baseline
+fn parse_baseline(contents: &str) -> Result<Baseline> {
+    let baseline: Baseline = toml::from_str(contents)?;
+
+    for entry in &baseline.suppress {
+        if entry.issue.is_some() == entry.path.is_some() {
+            anyhow::bail!(
+                "Baseline entries need exactly one of `issue` or `path` ({})",
+                entry.describe()
+            );
+        }
+        if entry.expires.is_some() && entry.expiry_date().is_none() {
+            anyhow::bail!("Baseline entry for {} has an invalid expiry date", entry.describe());
+        }
+    }
+
+    Ok(baseline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::sample_report;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn sample_findings() -> Findings {
+        let report = sample_report();
+        Findings {
+            out_of_sync: report.out_of_sync,
+            partially_deleted: report.partially_deleted,
+            synced: report.synced,
+            closed_with_tests: report.closed_with_tests,
+            issue_mismatches: report.issue_mismatches,
+            pull_request_references: report.pull_request_references,
+            suppressed: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_apply_baseline() {
+        let baseline = parse_baseline(
+            r#"
+            [[suppress]]
+            issue = 123456
+            reason = "Tracking issue"
+            expires = 2025-06-30
+
+            [[suppress]]
+            path = "tests/ui/crashes/ice-125000.rs"
+            reason = "Kept for the remaining test"
+
+            [[suppress]]
+            issue = 124000
+            reason = "Expired"
+            expires = 2024-01-01
+
+            [[suppress]]
+            path = "tests/crashes/126000.rs"
+            reason = "Directive is right"
+            "#,
+        )
+        .unwrap();
+
+        let today = date("2024-11-02");
+        assert_eq!(
+            baseline.expired(today).map(|e| e.describe()).collect::<Vec<_>>(),
+            vec!["issue #124000"]
+        );
+
+        let mut findings = sample_findings();
+        baseline.apply(&mut findings, today);
+
+        assert_eq!(
+            findings.out_of_sync.iter().map(|i| i.issue_number).collect::<Vec<_>>(),
+            vec![124000]
+        );
+        assert!(findings.partially_deleted.is_empty());
+        assert!(findings.issue_mismatches.is_empty());
+        assert_eq!(findings.closed_with_tests.as_ref().map(Vec::len), Some(1));
+        assert_eq!(
+            findings
+                .suppressed
+                .iter()
+                .map(|s| (s.category, s.issue_number))
+                .collect::<Vec<_>>(),
+            vec![
+                (FindingCategory::OutOfSync, Some(123456)),
+                (FindingCategory::Partial, Some(125000)),
+                (FindingCategory::Mismatch, None),
+            ]
+        );
+        assert_eq!(findings.suppressed[0].expires, Some(date("2025-06-30")));
+    }
+
+    #[test]
+    fn test_update_baseline() {
+        let mut baseline = Baseline {
+            suppress: vec![BaselineEntry {
+                issue: Some(124000),
+                path: None,
+                reason: "Expired, needs review".to_string(),
+                expires: Some("2024-01-01".parse().unwrap()),
+            }],
+        };
+
+        let today = date("2024-11-02");
+        let added = baseline.add_findings(&sample_findings(), today);
+
+        // 124000 is already listed (even though expired), the rest is new
+        assert_eq!(added, 5);
+        let contents = toml::to_string_pretty(&baseline).unwrap();
+        let reparsed = parse_baseline(&contents).unwrap();
+        assert_eq!(reparsed, baseline);
+
+        let mut findings = sample_findings();
+        baseline.apply(&mut findings, today);
+        assert_eq!(
+            findings.out_of_sync.iter().map(|i| i.issue_number).collect::<Vec<_>>(),
+            vec![124000]
+        );
+        assert_eq!(findings.closed_with_tests.map(|c| c.len()), Some(0));
+    }
+
+    #[test]
+    fn test_parse_baseline_errors() {
+        assert_eq!(parse_baseline("").unwrap(), Baseline::default());
+        assert!(parse_baseline("[[suppress]]\nreason = \"x\"\n").is_err());
+        assert!(parse_baseline("[[suppress]]\nissue = 1\npath = \"a.rs\"\nreason = \"x\"\n").is_err());
+        assert!(parse_baseline("[[suppress]]\nissue = 1\n").is_err());
+        assert!(parse_baseline("[[suppress]]\nissue = 1\nreason = \"x\"\nexpires = 12:00:00\n").is_err());
+    }
+}
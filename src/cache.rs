@@ -1,24 +1,160 @@
+use crate::git::DeletedCrashTest;
+use crate::github::{GitHubRepo, IssueListing};
+use crate::issues::{IssueMetadata, IssueState, IssueStateProvider, IssueStates};
+use crate::progress::Progress;
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-const CACHE_DIR: &str = ".cache";
+/// Directory of this tool within the user cache directory
+const CACHE_APP_DIR: &str = "rust-crash-audit";
 const CACHE_FILE: &str = "open_issues.json";
+const SCAN_CHECKPOINT_FILE: &str = "scan_checkpoints.json";
+/// Days between full listings of the open issues when refreshing the cache
+const FULL_SYNC_INTERVAL_DAYS: i64 = 7;
+
+/// Version of the issue cache layout, bumped whenever it changes
+/// Older files are migrated when loaded.
+pub const CACHE_SCHEMA_VERSION: u32 = 3;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CachedIssues {
+    pub schema_version: u32,
+    /// Repository the issues belong to
+    pub repository: GitHubRepo,
     pub timestamp: DateTime<Utc>,
+    /// Number of open issues
     pub issue_count: usize,
-    pub issue_numbers: Vec<u64>,
+    /// Every known issue, sorted by number; numbers missing here are closed
+    pub issues: Vec<IssueMetadata>,
+    /// Pull requests, open or closed, kept out of `issues`
+    /// (None for caches written before pull requests were told apart)
+    pub pull_request_numbers: Option<Vec<u64>>,
+    /// When every open issue was last listed (None if unknown)
+    /// Incremental refreshes only keep `timestamp` current.
+    #[serde(default)]
+    pub last_full_sync: Option<DateTime<Utc>>,
 }
 
 impl CachedIssues {
+    /// A cache holding a complete listing of open issues and pull requests
+    /// taken at `timestamp`
+    pub fn new(
+        github_repo: &GitHubRepo,
+        issues: impl IntoIterator<Item = IssueMetadata>,
+        pull_requests: impl IntoIterator<Item = u64>,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        let mut cached = Self {
+            schema_version: CACHE_SCHEMA_VERSION,
+            repository: github_repo.clone(),
+            timestamp,
+            issue_count: 0,
+            issues: issues.into_iter().collect(),
+            pull_request_numbers: Some(pull_requests.into_iter().collect()),
+            last_full_sync: Some(timestamp),
+        };
+        cached.normalize();
+        cached
+    }
+
+    /// Apply issues and pull requests that changed since the cache was written
+    /// Changed issues replace their cached entry, whether open or closed. Pull
+    /// requests stay known as such once closed or merged.
+    pub fn apply_updates(&mut self, updates: &IssueListing, timestamp: DateTime<Utc>) {
+        let mut issues: BTreeMap<u64, IssueMetadata> = self
+            .issues
+            .drain(..)
+            .map(|issue| (issue.number, issue))
+            .collect();
+        issues.extend(updates.issues.iter().map(|(&number, issue)| (number, issue.clone())));
+        self.issues = issues.into_values().collect();
+
+        self.pull_request_numbers
+            .get_or_insert_with(Vec::new)
+            .extend(&updates.pull_requests);
+
+        self.timestamp = timestamp;
+        self.normalize();
+    }
+
+    /// Carry over what a listing of open items can't show from the cache it
+    /// replaces: closed issues and pull requests that have since been closed or merged
+    /// Issues cached as open but missing from the listing are dropped, since
+    /// nothing is known about how they were closed.
+    pub fn keep_known(&mut self, previous: &CachedIssues) {
+        let closed: Vec<IssueMetadata> = previous
+            .issues
+            .iter()
+            .filter(|issue| issue.state == IssueState::Closed && self.issue(issue.number).is_none())
+            .cloned()
+            .collect();
+        self.issues.extend(closed);
+        self.pull_request_numbers
+            .get_or_insert_with(Vec::new)
+            .extend(previous.pull_request_numbers.iter().flatten());
+        self.normalize();
+    }
+
+    /// Whether the next refresh should list every open issue again rather
+    /// than only the changes, to correct drift (e.g. transferred or deleted issues)
+    pub fn needs_full_sync(&self, now: DateTime<Utc>) -> bool {
+        match self.last_full_sync {
+            Some(last_full_sync) if self.pull_request_numbers.is_some() => {
+                now.signed_duration_since(last_full_sync) >= chrono::Duration::days(FULL_SYNC_INTERVAL_DAYS)
+            }
+            _ => true,
+        }
+    }
+
+    /// Sort for consistency and recount the open issues
+    fn normalize(&mut self) {
+        self.issues.sort_by_key(|issue| issue.number);
+        self.issues.dedup_by_key(|issue| issue.number);
+        if let Some(pull_requests) = &mut self.pull_request_numbers {
+            pull_requests.sort();
+            pull_requests.dedup();
+        }
+        self.issue_count = self.issues.iter().filter(|issue| issue.state == IssueState::Open).count();
+    }
+
+    /// Numbers of the open issues
     pub fn to_hashset(&self) -> HashSet<u64> {
-        self.issue_numbers.iter().copied().collect()
+        self.issues
+            .iter()
+            .filter(|issue| issue.state == IssueState::Open)
+            .map(|issue| issue.number)
+            .collect()
+    }
+
+    /// What the cache knows about one issue
+    pub fn issue(&self, number: u64) -> Option<&IssueMetadata> {
+        self.issues
+            .binary_search_by_key(&number, |issue| issue.number)
+            .ok()
+            .map(|index| &self.issues[index])
+    }
+
+    pub fn pull_requests(&self) -> HashSet<u64> {
+        self.pull_request_numbers.iter().flatten().copied().collect()
+    }
+
+    /// States of `numbers`, with the metadata of those the cache has an entry for
+    pub fn issue_states(&self, numbers: &[u64], cached: bool) -> IssueStates {
+        let mut issue_states =
+            IssueStates::from_open_issues(&self.to_hashset(), &self.pull_requests(), numbers, self.timestamp, cached);
+        issue_states.issues = numbers
+            .iter()
+            .filter_map(|&number| self.issue(number))
+            .map(|issue| (issue.number, issue.clone()))
+            .collect();
+        issue_states
     }
 
     pub fn age(&self) -> Duration {
@@ -28,54 +164,417 @@ impl CachedIssues {
     }
 }
 
-/// Get the cache file path
-fn cache_path() -> PathBuf {
-    PathBuf::from(CACHE_DIR).join(CACHE_FILE)
+/// Schema version 1: open issue numbers only
+#[derive(Debug, Deserialize)]
+struct CachedIssuesV1 {
+    timestamp: DateTime<Utc>,
+    issue_numbers: Vec<u64>,
+    #[serde(default)]
+    pull_request_numbers: Option<Vec<u64>>,
+}
+
+impl CachedIssuesV1 {
+    fn migrate(self, github_repo: &GitHubRepo) -> CachedIssues {
+        let v1 = self;
+        let mut cached = CachedIssues {
+            schema_version: CACHE_SCHEMA_VERSION,
+            repository: github_repo.clone(),
+            timestamp: v1.timestamp,
+            issue_count: 0,
+            issues: v1.issue_numbers.into_iter().map(IssueMetadata::open).collect(),
+            pull_request_numbers: v1.pull_request_numbers,
+            last_full_sync: Some(v1.timestamp),
+        };
+        cached.normalize();
+        cached
+    }
+}
+
+/// Results of a previous history scan, keyed by repository path and pathspec
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+    pub repo_path: PathBuf,
+    pub pathspec: String,
+    /// Repository `known-bug` directives were resolved against (None in
+    /// checkpoints saved before it was recorded)
+    #[serde(default)]
+    pub repository: Option<GitHubRepo>,
+    /// PR recognizers the results were extracted with
+    pub pr_formats: Vec<String>,
+    /// Last commit processed (HEAD at the time of the scan)
+    pub head: String,
+    /// Oldest date covered by the scan (None = full history)
+    pub from: Option<NaiveDate>,
+    pub timestamp: DateTime<Utc>,
+    /// Deletions found, newest first, not filtered by end date
+    pub deleted_files: Vec<DeletedCrashTest>,
+}
+
+impl ScanCheckpoint {
+    fn matches(&self, repo_path: &Path, pathspec: &str) -> bool {
+        self.repo_path == repo_path && self.pathspec == pathspec
+    }
+}
+
+/// Default cache directory: `$XDG_CACHE_HOME/rust-crash-audit`, falling back
+/// to `~/.cache/rust-crash-audit`, or `.cache` if neither variable is set
+pub fn default_cache_dir() -> PathBuf {
+    let xdg_cache_home = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute());
+    let user_cache_dir = xdg_cache_home.or_else(|| {
+        env::var_os("HOME")
+            .filter(|home| !home.is_empty())
+            .map(|home| PathBuf::from(home).join(".cache"))
+    });
+
+    match user_cache_dir {
+        Some(dir) => dir.join(CACHE_APP_DIR),
+        None => PathBuf::from(".cache"),
+    }
+}
+
+/// Get the cache file path for a repository
+pub fn cache_path(cache_dir: &Path, github_repo: &GitHubRepo) -> PathBuf {
+    repo_cache_dir(cache_dir, github_repo).join(CACHE_FILE)
+}
+
+/// Get the cache directory for a repository (<cache dir>/<owner>/<repo>)
+fn repo_cache_dir(cache_dir: &Path, github_repo: &GitHubRepo) -> PathBuf {
+    cache_dir
+        .join(&github_repo.owner)
+        .join(&github_repo.name)
 }
 
 /// Check if cache exists
-pub fn cache_exists() -> bool {
-    cache_path().exists()
+pub fn cache_exists(cache_dir: &Path, github_repo: &GitHubRepo) -> bool {
+    cache_path(cache_dir, github_repo).exists()
 }
 
-/// Load cached open issues from file
-pub fn load_cache() -> Result<CachedIssues> {
-    let path = cache_path();
+/// Load cached issues from file, migrating older cache layouts
+pub fn load_cache(cache_dir: &Path, github_repo: &GitHubRepo, progress: &Progress) -> Result<CachedIssues> {
+    let path = cache_path(cache_dir, github_repo);
     let contents = fs::read_to_string(&path)
         .context("Failed to read cache file")?;
 
-    let cached: CachedIssues = serde_json::from_str(&contents)
+    let (cached, migrated_from) = parse_cache(&contents, Some(github_repo))
         .context("Failed to parse cache file")?;
+    if cached.repository != *github_repo {
+        anyhow::bail!("{} holds the issues of {}, not {}", path.display(), cached.repository, github_repo);
+    }
+
+    // Rewriting the migrated file is best effort, the migrated data is used either way
+    if let Some(version) = migrated_from {
+        match save_cache(cache_dir, github_repo, &cached) {
+            Ok(()) => progress.message(format!(
+                "Migrated {} from cache schema version {} to {}",
+                path.display(),
+                version,
+                CACHE_SCHEMA_VERSION
+            )),
+            Err(e) => progress.message(format!("Warning: failed to rewrite migrated cache {}: {:#}", path.display(), e)),
+        }
+    }
 
     Ok(cached)
 }
 
-/// Save open issues to cache file
-pub fn save_cache(issues: &HashSet<u64>) -> Result<()> {
-    // Create cache directory if it doesn't exist
-    let cache_dir = Path::new(CACHE_DIR);
-    if !cache_dir.exists() {
-        fs::create_dir_all(cache_dir)
+/// Parse a cache file of any known schema version
+/// Versions before 3 don't record their repository and are migrated as the
+/// cache of `legacy_repo`, or rejected without one. Also returns the version
+/// the file was migrated from, if it was not current.
+fn parse_cache(contents: &str, legacy_repo: Option<&GitHubRepo>) -> Result<(CachedIssues, Option<u64>)> {
+    let mut value: serde_json::Value = serde_json::from_str(contents)?;
+
+    // Version 1 files have no version field
+    let version = value.get("schema_version").map_or(Some(1), |v| v.as_u64());
+    let legacy_repo = || {
+        legacy_repo.with_context(|| {
+            format!(
+                "Cache schema version {} does not record its repository; export it again with this version of the tool",
+                version.unwrap_or_default()
+            )
+        })
+    };
+    match version {
+        Some(1) => {
+            let v1: CachedIssuesV1 = serde_json::from_value(value)?;
+            Ok((v1.migrate(legacy_repo()?), Some(1)))
+        }
+        // Version 3 added the repository
+        Some(2) => {
+            value["repository"] = serde_json::to_value(legacy_repo()?)?;
+            value["schema_version"] = CACHE_SCHEMA_VERSION.into();
+            Ok((serde_json::from_value(value)?, Some(2)))
+        }
+        Some(v) if v == u64::from(CACHE_SCHEMA_VERSION) => Ok((serde_json::from_value(value)?, None)),
+        Some(v) => anyhow::bail!(
+            "Unsupported cache schema version {} (this version of the tool reads up to {})",
+            v,
+            CACHE_SCHEMA_VERSION
+        ),
+        None => anyhow::bail!("Invalid cache schema version"),
+    }
+}
+
+/// Save cached issues to file
+pub fn save_cache(cache_dir: &Path, github_repo: &GitHubRepo, cached: &CachedIssues) -> Result<()> {
+    let json = serde_json::to_string_pretty(cached)
+        .context("Failed to serialize cache")?;
+
+    let path = cache_path(cache_dir, github_repo);
+    with_lock(&path, || write_atomically(&path, &json))
+        .context("Failed to write cache file")
+}
+
+/// Run `f` while holding an exclusive lock on `<path>.lock`, creating the
+/// parent directory if needed
+/// Other runs wait for the lock, so a read-modify-write cycle inside `f` (as
+/// for scan checkpoints) doesn't interleave with theirs. The issue cache is
+/// only locked while it is written: each save holds a complete refresh, so
+/// concurrent refreshes are last-writer-wins.
+fn with_lock<T>(path: &Path, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
             .context("Failed to create cache directory")?;
     }
 
-    // Convert HashSet to Vec for serialization
-    let mut issue_vec: Vec<u64> = issues.iter().copied().collect();
-    issue_vec.sort(); // Sort for consistency
+    let mut lock_name = path.as_os_str().to_owned();
+    lock_name.push(".lock");
+    let lock_file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_name)
+        .with_context(|| format!("Failed to open lock file {:?}", lock_name))?;
+    lock_file
+        .lock()
+        .with_context(|| format!("Failed to lock {:?}", lock_name))?;
+
+    // The lock is released when the file is closed
+    f()
+}
 
-    let cached = CachedIssues {
-        timestamp: Utc::now(),
-        issue_count: issues.len(),
-        issue_numbers: issue_vec,
+/// Write through a temporary file in the same directory and rename it into
+/// place, so readers never see a partially written file
+fn write_atomically(path: &Path, contents: &str) -> Result<()> {
+    let mut temp_name = path.as_os_str().to_owned();
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = PathBuf::from(temp_name);
+
+    let result = fs::write(&temp_path, contents).and_then(|()| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result.with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Repositories with cached issues, sorted
+pub fn cached_repos(cache_dir: &Path) -> Result<Vec<GitHubRepo>> {
+    let mut repos = Vec::new();
+    if !cache_dir.exists() {
+        return Ok(repos);
+    }
+
+    for owner in fs::read_dir(cache_dir).context("Failed to read cache directory")? {
+        let owner = owner?;
+        if !owner.file_type()?.is_dir() {
+            continue;
+        }
+        for name in fs::read_dir(owner.path())? {
+            let github_repo = GitHubRepo::new(
+                owner.file_name().to_string_lossy(),
+                name?.file_name().to_string_lossy(),
+            );
+            if cache_exists(cache_dir, &github_repo) {
+                repos.push(github_repo);
+            }
+        }
+    }
+
+    repos.sort_by(|a, b| (&a.owner, &a.name).cmp(&(&b.owner, &b.name)));
+    Ok(repos)
+}
+
+/// Delete the cached issues of one repository, or of every repository along
+/// with the scan checkpoints
+/// Only data files are removed; lock files and directories are left in place.
+/// Returns the number of files removed.
+pub fn clear_cache(cache_dir: &Path, github_repo: Option<&GitHubRepo>) -> Result<usize> {
+    let repos = match github_repo {
+        Some(github_repo) => vec![github_repo.clone()],
+        None => cached_repos(cache_dir)?,
     };
 
+    let mut files = Vec::new();
+    for github_repo in &repos {
+        files.push(cache_path(cache_dir, github_repo));
+    }
+    if github_repo.is_none() {
+        files.push(scan_checkpoint_path(cache_dir));
+    }
+
+    let mut removed = 0;
+    for path in files {
+        with_lock(&path, || {
+            if path.exists() {
+                fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+                removed += 1;
+            }
+            Ok(())
+        })?;
+        // Lock files stay: if one were removed while another run holds it, a
+        // third run would lock a new file and both would go ahead
+    }
+
+    Ok(removed)
+}
+
+/// Write a repository's cached issues to a file, e.g. to seed another machine
+pub fn export_cache(
+    cache_dir: &Path,
+    github_repo: &GitHubRepo,
+    path: &Path,
+    progress: &Progress,
+) -> Result<CachedIssues> {
+    let cached = load_cache(cache_dir, github_repo, progress)
+        .context("Failed to load cache")?;
+
     let json = serde_json::to_string_pretty(&cached)
         .context("Failed to serialize cache")?;
+    fs::write(path, json)
+        .with_context(|| format!("Failed to write {:?}", path))?;
+
+    Ok(cached)
+}
+
+/// Replace a repository's cached issues with an exported file
+/// The file must hold the issues of the same repository.
+pub fn import_cache(cache_dir: &Path, github_repo: &GitHubRepo, path: &Path) -> Result<CachedIssues> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    let (mut cached, _) = parse_cache(&contents, None)
+        .with_context(|| format!("Failed to parse {:?}", path))?;
+    if cached.repository != *github_repo {
+        anyhow::bail!(
+            "{} holds the issues of {}, not {}",
+            path.display(),
+            cached.repository,
+            github_repo
+        );
+    }
+    cached.normalize();
+
+    save_cache(cache_dir, github_repo, &cached)?;
+    Ok(cached)
+}
+
+/// Issue states from the cached open issues, without network access
+#[derive(Debug, Clone)]
+pub struct CacheProvider {
+    cache_dir: PathBuf,
+    github_repo: GitHubRepo,
+    progress: Progress,
+}
+
+impl CacheProvider {
+    pub fn new(cache_dir: impl Into<PathBuf>, github_repo: GitHubRepo) -> Self {
+        Self {
+            cache_dir: cache_dir.into(),
+            github_repo,
+            progress: Progress::default(),
+        }
+    }
+
+    /// Where progress messages and warnings go (default: nowhere)
+    pub fn progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+}
+
+#[async_trait]
+impl IssueStateProvider for CacheProvider {
+    async fn issue_states(&self, numbers: &[u64]) -> Result<IssueStates> {
+        if !cache_exists(&self.cache_dir, &self.github_repo) {
+            anyhow::bail!(
+                "No cached issues for {} ({} is missing)",
+                self.github_repo,
+                cache_path(&self.cache_dir, &self.github_repo).display()
+            );
+        }
+
+        let cached = load_cache(&self.cache_dir, &self.github_repo, &self.progress)
+            .context("Failed to load cache")?;
+
+        let age = format_duration(cached.age());
+        self.progress.message(format!("Using cached data (updated {} ago)\n", age));
+        if cached.pull_request_numbers.is_none() {
+            self.progress.message(
+                "Warning: the cache predates pull request detection, so open pull requests count as open issues\n",
+            );
+        }
+
+        Ok(cached.issue_states(numbers, true))
+    }
+}
+
+/// Get the scan checkpoint file path
+pub fn scan_checkpoint_path(cache_dir: &Path) -> PathBuf {
+    cache_dir.join(SCAN_CHECKPOINT_FILE)
+}
+
+/// Load all saved scan checkpoints
+pub fn load_scan_checkpoints(cache_dir: &Path) -> Result<Vec<ScanCheckpoint>> {
+    let path = scan_checkpoint_path(cache_dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .context("Failed to read scan checkpoint file")?;
+
+    let checkpoints: Vec<ScanCheckpoint> = serde_json::from_str(&contents)
+        .context("Failed to parse scan checkpoint file")?;
+
+    Ok(checkpoints)
+}
+
+/// Load the scan checkpoint for a repository and pathspec, if any
+pub fn load_scan_checkpoint(cache_dir: &Path, repo_path: &Path, pathspec: &str) -> Result<Option<ScanCheckpoint>> {
+    let repo_path = repo_path.canonicalize().unwrap_or_else(|_| repo_path.to_path_buf());
+
+    Ok(load_scan_checkpoints(cache_dir)?
+        .into_iter()
+        .find(|checkpoint| checkpoint.matches(&repo_path, pathspec)))
+}
+
+/// Save a scan checkpoint, replacing any previous one for the same repository and pathspec
+pub fn save_scan_checkpoint(cache_dir: &Path, checkpoint: ScanCheckpoint) -> Result<()> {
+    let path = scan_checkpoint_path(cache_dir);
 
-    fs::write(cache_path(), json)
-        .context("Failed to write cache file")?;
+    // Hold the lock from reading to writing so that checkpoints saved by
+    // concurrent runs for other repositories are kept
+    with_lock(&path, || {
+        // An unreadable checkpoint file is just replaced
+        let checkpoints = upsert_checkpoint(load_scan_checkpoints(cache_dir).unwrap_or_default(), checkpoint);
 
-    Ok(())
+        let json = serde_json::to_string_pretty(&checkpoints)
+            .context("Failed to serialize scan checkpoints")?;
+
+        write_atomically(&path, &json)
+    })
+    .context("Failed to write scan checkpoint file")
+}
+
+/// Replace the checkpoint with the same key, or append it
+fn upsert_checkpoint(
+    mut checkpoints: Vec<ScanCheckpoint>,
+    checkpoint: ScanCheckpoint,
+) -> Vec<ScanCheckpoint> {
+    checkpoints.retain(|existing| !existing.matches(&checkpoint.repo_path, &checkpoint.pathspec));
+    checkpoints.push(checkpoint);
+    checkpoints
 }
 
 /// Format duration in human-readable form
@@ -96,9 +595,269 @@ pub fn format_duration(duration: Duration) -> String {
     }
 }
 
+/// Parse a duration like `30m`, `6h`, `2d` or `1w`
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit())
+        .with_context(|| format!("Missing unit in duration {:?} (expected s, m, h, d or w)", value))?;
+    let (amount, unit) = value.split_at(unit_start);
+
+    let amount: u64 = amount
+        .parse()
+        .with_context(|| format!("Invalid duration {:?}", value))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        _ => anyhow::bail!("Unknown unit {:?} in duration {:?} (expected s, m, h, d or w)", unit, value),
+    };
+
+    let secs = amount
+        .checked_mul(unit_secs)
+        .with_context(|| format!("Duration {:?} is too long", value))?;
+    Ok(Duration::from_secs(secs))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::issues::StateReason;
+
+    fn checkpoint(repo_path: &str, pathspec: &str, head: &str) -> ScanCheckpoint {
+        ScanCheckpoint {
+            repo_path: PathBuf::from(repo_path),
+            pathspec: pathspec.to_string(),
+            repository: Some(GitHubRepo::default()),
+            pr_formats: vec!["bors".to_string()],
+            head: head.to_string(),
+            from: None,
+            timestamp: Utc::now(),
+            deleted_files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_upsert_checkpoint() {
+        let checkpoints = vec![
+            checkpoint("/repos/rust", "tests/crashes/*.rs", "aaaa"),
+            checkpoint("/repos/clippy", "tests/crashes/*.rs", "bbbb"),
+        ];
+
+        let updated = upsert_checkpoint(checkpoints, checkpoint("/repos/rust", "tests/crashes/*.rs", "cccc"));
+        assert_eq!(updated.len(), 2);
+        assert!(updated.iter().any(|c| c.repo_path == Path::new("/repos/rust") && c.head == "cccc"));
+        assert!(updated.iter().any(|c| c.repo_path == Path::new("/repos/clippy") && c.head == "bbbb"));
+
+        let updated = upsert_checkpoint(updated, checkpoint("/repos/rust", "tests/ui/*.rs", "dddd"));
+        assert_eq!(updated.len(), 3);
+    }
+
+    #[test]
+    fn test_concurrent_saves() {
+        let cache_dir = tempfile::TempDir::new().unwrap();
+
+        // Each thread adds its own checkpoint; the lock keeps them all
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let cache_dir = cache_dir.path();
+                scope.spawn(move || {
+                    let checkpoint = checkpoint(&format!("/repos/{}", i), "tests/crashes/*.rs", "aaaa");
+                    save_scan_checkpoint(cache_dir, checkpoint).unwrap();
+                });
+            }
+        });
+        assert_eq!(load_scan_checkpoints(cache_dir.path()).unwrap().len(), 8);
+
+        let github_repo = GitHubRepo::new("example", "project");
+        let cached = CachedIssues::new(&github_repo, [IssueMetadata::open(100)], [], Utc::now());
+        save_cache(cache_dir.path(), &github_repo, &cached).unwrap();
+        assert_eq!(load_cache(cache_dir.path(), &github_repo, &Progress::default()).unwrap().to_hashset(), [100].into_iter().collect());
+
+        // Only the cache files and their locks are left behind, no temporary files
+        let mut names: Vec<String> = fs::read_dir(cache_dir.path().join("example/project"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["open_issues.json", "open_issues.json.lock"]);
+    }
+
+    #[test]
+    fn test_clear_cache() {
+        let cache_dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = cache_dir.path();
+        let rust = GitHubRepo::new("rust-lang", "rust");
+        let clippy = GitHubRepo::new("rust-lang", "rust-clippy");
+        for github_repo in [&rust, &clippy] {
+            let cached = CachedIssues::new(github_repo, [IssueMetadata::open(100)], [], Utc::now());
+            save_cache(cache_dir, github_repo, &cached).unwrap();
+        }
+        save_scan_checkpoint(cache_dir, checkpoint("/repos/rust", "tests/crashes/*.rs", "aaaa")).unwrap();
+        fs::write(cache_dir.join("unrelated.txt"), "keep me").unwrap();
+
+        assert_eq!(cached_repos(cache_dir).unwrap(), vec![rust.clone(), clippy.clone()]);
+
+        assert_eq!(clear_cache(cache_dir, Some(&clippy)).unwrap(), 1);
+        assert_eq!(cached_repos(cache_dir).unwrap(), vec![rust.clone()]);
+        assert!(!cache_path(cache_dir, &clippy).exists());
+        assert!(cache_dir.join("rust-lang/rust-clippy/open_issues.json.lock").exists());
+
+        assert_eq!(clear_cache(cache_dir, None).unwrap(), 2);
+        assert!(cached_repos(cache_dir).unwrap().is_empty());
+        assert!(!scan_checkpoint_path(cache_dir).exists());
+        assert!(cache_dir.join("unrelated.txt").exists());
+    }
+
+    #[test]
+    fn test_import_cache() {
+        let cache_dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = cache_dir.path();
+        let rust = GitHubRepo::new("rust-lang", "rust");
+        let clippy = GitHubRepo::new("rust-lang", "rust-clippy");
+        save_cache(cache_dir, &rust, &CachedIssues::new(&rust, [IssueMetadata::open(100)], [], Utc::now())).unwrap();
+
+        let exported = cache_dir.join("export.json");
+        export_cache(cache_dir, &rust, &exported, &Progress::default()).unwrap();
+
+        // The export only goes back into the cache of its own repository
+        let error = import_cache(cache_dir, &clippy, &exported).unwrap_err();
+        assert!(error.to_string().contains("holds the issues of rust-lang/rust, not rust-lang/rust-clippy"), "{}", error);
+        assert!(!cache_exists(cache_dir, &clippy));
+
+        clear_cache(cache_dir, Some(&rust)).unwrap();
+        import_cache(cache_dir, &rust, &exported).unwrap();
+        assert_eq!(load_cache(cache_dir, &rust, &Progress::default()).unwrap().to_hashset(), [100].into_iter().collect());
+
+        // A cache file copied to another repository's place isn't used for it either
+        fs::create_dir_all(repo_cache_dir(cache_dir, &clippy)).unwrap();
+        fs::copy(cache_path(cache_dir, &rust), cache_path(cache_dir, &clippy)).unwrap();
+        assert!(load_cache(cache_dir, &clippy, &Progress::default()).is_err());
+    }
+
+    #[test]
+    fn test_parse_cache() {
+        let v1 = r#"{"timestamp": "2024-06-01T12:00:00Z", "issue_count": 2, "issue_numbers": [300, 100]}"#;
+        let github_repo = GitHubRepo::new("rust-lang", "rust");
+        let (cached, migrated_from) = parse_cache(v1, Some(&github_repo)).unwrap();
+        assert_eq!(migrated_from, Some(1));
+        assert_eq!(cached.schema_version, CACHE_SCHEMA_VERSION);
+        assert_eq!(cached.repository, github_repo);
+        assert_eq!(cached.issues, vec![IssueMetadata::open(100), IssueMetadata::open(300)]);
+        assert_eq!(cached.issue_count, 2);
+        assert!(cached.pull_request_numbers.is_none());
+
+        // A migrated cache reads back as the current version
+        let json = serde_json::to_string(&cached).unwrap();
+        let (reread, migrated_from) = parse_cache(&json, None).unwrap();
+        assert_eq!(migrated_from, None);
+        assert_eq!(reread.issues, cached.issues);
+        assert_eq!(reread.repository, github_repo);
+
+        let v2 = r#"{
+            "schema_version": 2,
+            "timestamp": "2024-06-01T12:00:00Z",
+            "issue_count": 1,
+            "issues": [
+                {"number": 100, "title": "ICE: broken", "state": "open", "state_reason": null,
+                 "labels": ["I-ICE"], "closed_at": null, "updated_at": "2024-05-30T08:00:00Z"},
+                {"number": 200, "title": "Dup", "state": "closed", "state_reason": "duplicate",
+                 "labels": [], "closed_at": "2024-05-31T08:00:00Z", "updated_at": "2024-05-31T08:00:00Z"}
+            ],
+            "pull_request_numbers": [150]
+        }"#;
+        let (cached, migrated_from) = parse_cache(v2, Some(&github_repo)).unwrap();
+        assert_eq!(migrated_from, Some(2));
+        assert_eq!(cached.repository, github_repo);
+        assert_eq!(cached.to_hashset(), [100].into_iter().collect());
+        assert_eq!(cached.issues[1].state_reason, Some(StateReason::Duplicate));
+        assert_eq!(cached.pull_requests(), [150].into_iter().collect());
+
+        // Without a repository to assume, older files can't be told apart
+        let error = parse_cache(v2, None).unwrap_err();
+        assert!(error.to_string().contains("does not record its repository"), "{}", error);
+
+        let future = r#"{"schema_version": 99, "timestamp": "2024-06-01T12:00:00Z"}"#;
+        assert!(parse_cache(future, None).unwrap_err().to_string().contains("99"));
+    }
+
+    #[test]
+    fn test_needs_full_sync() {
+        let synced_at: DateTime<Utc> = "2024-06-01T00:00:00Z".parse().unwrap();
+        let mut cached = CachedIssues::new(&GitHubRepo::default(), [IssueMetadata::open(100)], [], synced_at);
+
+        assert!(!cached.needs_full_sync(synced_at + chrono::Duration::days(6)));
+        assert!(cached.needs_full_sync(synced_at + chrono::Duration::days(FULL_SYNC_INTERVAL_DAYS)));
+
+        // Incremental refreshes don't postpone the full resync
+        cached.apply_updates(&IssueListing::default(), synced_at + chrono::Duration::days(5));
+        assert!(cached.needs_full_sync(synced_at + chrono::Duration::days(8)));
+
+        // Nor can a cache without pull requests be refreshed incrementally
+        cached.pull_request_numbers = None;
+        assert!(cached.needs_full_sync(synced_at));
+        cached.last_full_sync = None;
+        cached.pull_request_numbers = Some(Vec::new());
+        assert!(cached.needs_full_sync(synced_at));
+    }
+
+    #[test]
+    fn test_pull_requests_stay_known() {
+        let synced_at: DateTime<Utc> = "2024-06-01T00:00:00Z".parse().unwrap();
+        let mut cached = CachedIssues::new(&GitHubRepo::default(), [IssueMetadata::open(100)], [150], synced_at);
+
+        // A merged pull request shows up in the changes and is kept
+        let mut updates = IssueListing::default();
+        updates.pull_requests.insert(160);
+        cached.apply_updates(&updates, synced_at + chrono::Duration::days(1));
+        assert_eq!(cached.pull_requests(), [150, 160].into_iter().collect());
+
+        // A full listing of open items no longer has them, but they carry over
+        let mut resynced = CachedIssues::new(&GitHubRepo::default(), [IssueMetadata::open(100)], [170], synced_at + chrono::Duration::days(7));
+        resynced.keep_known(&cached);
+        assert_eq!(resynced.pull_requests(), [150, 160, 170].into_iter().collect());
+    }
+
+    #[test]
+    fn test_keep_known() {
+        let closed = |number| IssueMetadata {
+            state: IssueState::Closed,
+            state_reason: Some(StateReason::Duplicate),
+            ..IssueMetadata::open(number)
+        };
+        let synced_at: DateTime<Utc> = "2024-06-01T00:00:00Z".parse().unwrap();
+        let previous = CachedIssues::new(&GitHubRepo::default(), [IssueMetadata::open(100), IssueMetadata::open(101), closed(200), closed(300)], [], synced_at);
+
+        // 101 is gone from the open listing and 300 was reopened
+        let mut resynced = CachedIssues::new(&GitHubRepo::default(), [IssueMetadata::open(100), IssueMetadata::open(300)], [], synced_at);
+        resynced.keep_known(&previous);
+
+        assert_eq!(resynced.issues.iter().map(|issue| issue.number).collect::<Vec<_>>(), vec![100, 200, 300]);
+        assert_eq!(resynced.to_hashset(), [100, 300].into_iter().collect());
+        assert_eq!(resynced.issue(200).unwrap().state_reason, Some(StateReason::Duplicate));
+        assert_eq!(resynced.issue_count, 2);
+
+        let issue_states = resynced.issue_states(&[100, 101, 200], true);
+        assert_eq!(issue_states.states[&101], IssueState::Closed);
+        assert_eq!(issue_states.issues.keys().copied().collect::<Vec<_>>(), vec![100, 200]);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45s").unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_duration("6h").unwrap(), Duration::from_secs(6 * 3600));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::from_secs(2 * 86400));
+        assert_eq!(parse_duration("1w").unwrap(), Duration::from_secs(7 * 86400));
+
+        assert!(parse_duration("6").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("6 hours").is_err());
+        assert!(parse_duration("-1d").is_err());
+        assert!(parse_duration("99999999999999w").is_err());
+    }
 
     #[test]
     fn test_format_duration() {
@@ -0,0 +1,185 @@
+use crate::cache;
+use crate::pr::PrRecognizer;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Default config file, looked up in the current directory
+pub const DEFAULT_CONFIG_FILE: &str = ".crash-audit.toml";
+
+/// Tool configuration loaded from `.crash-audit.toml`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Directories containing crash tests
+    #[serde(default = "default_watch_groups")]
+    pub watch: Vec<WatchGroup>,
+    /// Commit message formats to recognize PR numbers from, in priority order
+    pub pr_formats: Option<Vec<PrRecognizer>>,
+    /// Refresh the issue cache once it is older than this (e.g. "6h", "2d")
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub max_cache_age: Option<Duration>,
+}
+
+/// A directory of crash tests and how to map its files to issues
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchGroup {
+    /// Name used to tag results and in the report
+    pub name: String,
+    /// Directory relative to the repository root
+    pub dir: String,
+    /// Filename pattern within the directory
+    #[serde(default = "default_glob")]
+    pub glob: String,
+    /// How to get an issue number from a filename
+    #[serde(default)]
+    pub issue: IssueRule,
+}
+
+/// How to extract an issue number from a crash test filename
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssueRule {
+    /// Digits at the start of the filename: `12345.rs`, `12345-foo.rs`
+    #[default]
+    LeadingNumber,
+    /// First run of digits anywhere in the filename: `ice-12345.rs`
+    AnyNumber,
+    /// Ignore the filename, use only `known-bug` directives
+    DirectiveOnly,
+}
+
+impl WatchGroup {
+    /// Git pathspec matching the files in this group
+    pub fn pathspec(&self) -> String {
+        format!("{}/{}", self.dir.trim_end_matches('/'), self.glob)
+    }
+
+    /// Whether a repository-relative path belongs to this group's directory
+    pub fn contains_dir_of(&self, path: &str) -> bool {
+        Path::new(path).parent() == Some(Path::new(self.dir.trim_end_matches('/')))
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            watch: default_watch_groups(),
+            pr_formats: None,
+            max_cache_age: None,
+        }
+    }
+}
+
+fn default_watch_groups() -> Vec<WatchGroup> {
+    vec![WatchGroup {
+        name: "crashes".to_string(),
+        dir: "tests/crashes".to_string(),
+        glob: default_glob(),
+        issue: IssueRule::LeadingNumber,
+    }]
+}
+
+fn default_glob() -> String {
+    "*.rs".to_string()
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    cache::parse_duration(&value)
+        .map(Some)
+        .map_err(|e| serde::de::Error::custom(format!("{:#}", e)))
+}
+
+/// Load configuration from the given file, or from `.crash-audit.toml` if it exists
+pub fn load_config(path: Option<&Path>) -> Result<Config> {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let default_path = Path::new(DEFAULT_CONFIG_FILE);
+            if !default_path.exists() {
+                return Ok(Config::default());
+            }
+            default_path
+        }
+    };
+
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file {:?}", path))?;
+
+    parse_config(&contents)
+        .with_context(|| format!("Failed to parse config file {:?}", path))
+}
+
+/// Parse and validate configuration
+fn parse_config(contents: &str) -> Result<Config> {
+    let config: Config = toml::from_str(contents)?;
+
+    if config.watch.is_empty() {
+        anyhow::bail!("At least one [[watch]] directory is required");
+    }
+    for (i, group) in config.watch.iter().enumerate() {
+        if config.watch[..i].iter().any(|other| other.name == group.name) {
+            anyhow::bail!("Duplicate watch group name: {}", group.name);
+        }
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = parse_config(
+            r#"
+            pr_formats = ["squash", "github-merge"]
+            max_cache_age = "2d"
+
+            [[watch]]
+            name = "crashes"
+            dir = "tests/crashes"
+
+            [[watch]]
+            name = "clippy"
+            dir = "tests/ui/crashes/"
+            glob = "ice-*.rs"
+            issue = "any-number"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.pr_formats,
+            Some(vec![PrRecognizer::Squash, PrRecognizer::GithubMerge])
+        );
+        assert_eq!(config.max_cache_age, Some(Duration::from_secs(2 * 86400)));
+        assert_eq!(config.watch.len(), 2);
+        assert_eq!(config.watch[0], default_watch_groups()[0]);
+        assert_eq!(config.watch[1].pathspec(), "tests/ui/crashes/ice-*.rs");
+        assert_eq!(config.watch[1].issue, IssueRule::AnyNumber);
+        assert!(config.watch[1].contains_dir_of("tests/ui/crashes/ice-1.rs"));
+        assert!(!config.watch[1].contains_dir_of("tests/ui/ice-1.rs"));
+    }
+
+    #[test]
+    fn test_parse_config_defaults_and_errors() {
+        let config = parse_config("").unwrap();
+        assert_eq!(config.watch, default_watch_groups());
+        assert_eq!(config.pr_formats, None);
+        assert_eq!(config.max_cache_age, None);
+
+        assert!(parse_config("max_cache_age = \"soon\"").is_err());
+        assert!(parse_config("watch = []").is_err());
+        assert!(parse_config(
+            "[[watch]]\nname = \"a\"\ndir = \"x\"\n[[watch]]\nname = \"a\"\ndir = \"y\"\n"
+        )
+        .is_err());
+        assert!(parse_config("[[watch]]\nname = \"a\"\ndir = \"x\"\nissue = \"bogus\"\n").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+use crate::model::{AuditReport, IssueDeletions, SCHEMA_VERSION};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::Path;
+
+/// What changed between two audit runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportDiff {
+    pub old_generated_at: DateTime<Utc>,
+    pub new_generated_at: DateTime<Utc>,
+    /// Issues out of sync now that were not before
+    pub newly_out_of_sync: Vec<IssueDeletions>,
+    /// Issues that were out of sync and no longer are
+    pub resolved: Vec<ResolvedIssue>,
+    /// Issues that went from partial cleanup to all tests deleted
+    pub partial_completed: Vec<IssueDeletions>,
+    /// Issues out of sync in both runs
+    pub still_out_of_sync: usize,
+}
+
+/// An issue that is no longer out of sync
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedIssue {
+    pub issue_number: u64,
+    pub resolution: Resolution,
+}
+
+/// Why an issue is no longer out of sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Resolution {
+    /// The issue was closed
+    Closed,
+    /// Some of its crash tests are back
+    TestsRestored,
+    /// The issue was closed and some of its crash tests are back
+    ClosedAndTestsRestored,
+    /// The baseline file now suppresses it
+    Suppressed,
+    /// It is not in the new report at all (e.g. a different date range)
+    NotReported,
+}
+
+impl Resolution {
+    fn describe(self) -> &'static str {
+        match self {
+            Resolution::Closed => "issue closed",
+            Resolution::TestsRestored => "tests restored",
+            Resolution::ClosedAndTestsRestored => "issue closed and tests restored",
+            Resolution::Suppressed => "suppressed by baseline",
+            Resolution::NotReported => "no longer reported",
+        }
+    }
+}
+
+/// Load a report saved with `--format json` or `--save-report`
+pub fn load_report(path: &Path) -> Result<AuditReport> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read report {:?}", path))?;
+
+    let value: serde_json::Value = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse report {:?}", path))?;
+    let version = value.get("schema_version").and_then(|v| v.as_u64());
+    if version != Some(SCHEMA_VERSION.into()) {
+        anyhow::bail!(
+            "Report {:?} has schema version {}, expected {}",
+            path,
+            version.map(|v| v.to_string()).unwrap_or_else(|| "unknown".to_string()),
+            SCHEMA_VERSION
+        );
+    }
+
+    serde_json::from_value(value)
+        .with_context(|| format!("Failed to parse report {:?}", path))
+}
+
+/// Compare two reports of the same repository
+pub fn diff_reports(old: &AuditReport, new: &AuditReport) -> ReportDiff {
+    let contains = |issues: &[IssueDeletions], number: u64| issues.iter().any(|i| i.issue_number == number);
+
+    let newly_out_of_sync = new
+        .out_of_sync
+        .iter()
+        .filter(|issue| !contains(&old.out_of_sync, issue.issue_number))
+        .cloned()
+        .collect();
+
+    let resolved = old
+        .out_of_sync
+        .iter()
+        .filter(|issue| !contains(&new.out_of_sync, issue.issue_number))
+        .map(|issue| {
+            let number = issue.issue_number;
+            let resolution = if contains(&new.synced, number) {
+                Resolution::Closed
+            } else if let Some(partial) = new.partially_deleted.iter().find(|i| i.issue_number == number) {
+                // Closed issues with remaining tests are filed as partial too
+                if partial.issue_open {
+                    Resolution::TestsRestored
+                } else {
+                    Resolution::ClosedAndTestsRestored
+                }
+            } else if new.suppressed.iter().any(|s| s.issue_number == Some(number)) {
+                Resolution::Suppressed
+            } else {
+                Resolution::NotReported
+            };
+            ResolvedIssue { issue_number: number, resolution }
+        })
+        .collect();
+
+    let partial_completed = new
+        .out_of_sync
+        .iter()
+        .chain(&new.synced)
+        .filter(|issue| contains(&old.partially_deleted, issue.issue_number))
+        .cloned()
+        .collect();
+
+    let still_out_of_sync = new
+        .out_of_sync
+        .iter()
+        .filter(|issue| contains(&old.out_of_sync, issue.issue_number))
+        .count();
+
+    ReportDiff {
+        old_generated_at: old.metadata.generated_at,
+        new_generated_at: new.metadata.generated_at,
+        newly_out_of_sync,
+        resolved,
+        partial_completed,
+        still_out_of_sync,
+    }
+}
+
+/// Write the human-readable list of changes
+pub fn write_text(out: &mut impl Write, diff: &ReportDiff, new: &AuditReport) -> fmt::Result {
+    let github_repo = &new.metadata.repository;
+
+    writeln!(
+        out,
+        "Changes between {} and {}:",
+        diff.old_generated_at.format("%Y-%m-%d %H:%M UTC"),
+        diff.new_generated_at.format("%Y-%m-%d %H:%M UTC")
+    )?;
+    writeln!(out)?;
+
+    if !diff.newly_out_of_sync.is_empty() {
+        writeln!(out, "⚠️  Newly out of sync ({}):", diff.newly_out_of_sync.len())?;
+        for issue in &diff.newly_out_of_sync {
+            let files: Vec<&str> = issue.files.iter().map(|f| f.file_path.as_str()).collect();
+            writeln!(out, "  • Issue #{}: {}", issue.issue_number, files.join(", "))?;
+            writeln!(out, "    Issue: {}", github_repo.issue_url(issue.issue_number))?;
+        }
+        writeln!(out)?;
+    }
+
+    if !diff.resolved.is_empty() {
+        writeln!(out, "✅ Resolved ({}):", diff.resolved.len())?;
+        for resolved in &diff.resolved {
+            writeln!(
+                out,
+                "  • Issue #{}: {}",
+                resolved.issue_number,
+                resolved.resolution.describe()
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    if !diff.partial_completed.is_empty() {
+        writeln!(out, "🏁 Partial cleanups completed ({}):", diff.partial_completed.len())?;
+        for issue in &diff.partial_completed {
+            let state = if issue.issue_open { "still open" } else { "closed" };
+            writeln!(out, "  • Issue #{}: all tests deleted, issue {}", issue.issue_number, state)?;
+        }
+        writeln!(out)?;
+    }
+
+    if diff.newly_out_of_sync.is_empty() && diff.resolved.is_empty() && diff.partial_completed.is_empty() {
+        writeln!(out, "No changes.")?;
+        writeln!(out)?;
+    }
+
+    writeln!(out, "Still out of sync: {}", diff.still_out_of_sync)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::sample_report;
+    use crate::model::{FindingCategory, SuppressedFinding};
+
+    #[test]
+    fn test_diff_reports() {
+        let mut old = sample_report();
+        let mut new = sample_report();
+
+        // 123456 got closed, 124000 became suppressed, 125000 lost its last test,
+        // 126000 got closed with a test restored and 127000 just got a test restored
+        for number in [126000, 127000] {
+            let mut issue = old.out_of_sync[0].clone();
+            issue.issue_number = number;
+            old.out_of_sync.push(issue.clone());
+            new.partially_deleted.push(IssueDeletions {
+                issue_open: number == 127000,
+                remaining_files: 1,
+                ..issue
+            });
+        }
+        let closed = new.out_of_sync.remove(0);
+        new.synced.push(IssueDeletions { issue_open: false, ..closed });
+        new.out_of_sync.remove(0);
+        new.suppressed.push(SuppressedFinding {
+            category: FindingCategory::OutOfSync,
+            issue_number: Some(124000),
+            files: Vec::new(),
+            reason: "Tracking".to_string(),
+            expires: None,
+        });
+        let mut completed = new.partially_deleted.remove(0);
+        completed.remaining_files = 0;
+        new.out_of_sync.push(completed);
+
+        let diff = diff_reports(&old, &new);
+        assert_eq!(
+            diff.newly_out_of_sync.iter().map(|i| i.issue_number).collect::<Vec<_>>(),
+            vec![125000]
+        );
+        assert_eq!(
+            diff.resolved,
+            vec![
+                ResolvedIssue { issue_number: 123456, resolution: Resolution::Closed },
+                ResolvedIssue { issue_number: 124000, resolution: Resolution::Suppressed },
+                ResolvedIssue { issue_number: 126000, resolution: Resolution::ClosedAndTestsRestored },
+                ResolvedIssue { issue_number: 127000, resolution: Resolution::TestsRestored },
+            ]
+        );
+        assert_eq!(
+            diff.partial_completed.iter().map(|i| i.issue_number).collect::<Vec<_>>(),
+            vec![125000]
+        );
+        assert_eq!(diff.still_out_of_sync, 0);
+
+        let unchanged = diff_reports(&old, &old);
+        assert!(unchanged.newly_out_of_sync.is_empty() && unchanged.resolved.is_empty());
+        assert_eq!(unchanged.still_out_of_sync, 4);
+    }
+}
@@ -1,27 +1,315 @@
+use crate::cache::ScanCheckpoint;
+use crate::config::{IssueRule, WatchGroup};
+use crate::github::GitHubRepo;
+use crate::header::{self, CommentSyntax};
+use crate::pr::{PrExtractor, PrRecognizer};
+use crate::progress::Progress;
 use anyhow::{Context, Result};
 use chrono::NaiveDate;
-use git2::{Delta, DiffOptions, Repository};
-use std::collections::HashSet;
+use git2::{
+    Commit, Delta, Diff, DiffFindOptions, DiffFormat, DiffOptions, Oid, Pathspec, PathspecFlags,
+    Repository, Tree,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-#[derive(Debug, Clone)]
+/// Settings for a history scan
+#[derive(Debug, Clone, Copy)]
+pub struct ScanOptions<'a> {
+    /// Crash test directories to watch
+    pub groups: &'a [WatchGroup],
+    pub pr_extractor: &'a PrExtractor,
+    /// Repository that `known-bug` directives refer to
+    pub github_repo: &'a GitHubRepo,
+    /// Number of worker threads diffing commits
+    pub jobs: usize,
+    pub progress: &'a Progress,
+}
+
+/// Key identifying a set of watch groups, used to match scan checkpoints
+pub fn scan_key(groups: &[WatchGroup]) -> String {
+    groups
+        .iter()
+        .map(|group| format!("{}={}:{:?}", group.name, group.pathspec(), group.issue))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Find the watch group a repository-relative path belongs to
+fn group_for_path<'a>(groups: &'a [WatchGroup], path: &str) -> Option<&'a WatchGroup> {
+    let file_name = Path::new(path).file_name()?;
+    groups.iter().find(|group| {
+        group.contains_dir_of(path) && matches_glob(&group.glob, Path::new(file_name))
+    })
+}
+
+/// Match a filename against a group's glob, using git's pathspec rules
+fn matches_glob(glob: &str, file_name: &Path) -> bool {
+    Pathspec::new([glob])
+        .map(|pathspec| pathspec.matches_path(file_name, PathspecFlags::DEFAULT))
+        .unwrap_or(false)
+}
+
+/// Number of commit chunks handed out per worker thread
+const CHUNKS_PER_JOB: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DeletedCrashTest {
     pub file_path: String,
-    pub issue_number: u64,
+    /// Name of the watch group the file belonged to
+    pub group: String,
+    pub issues: Vec<IssueRef>,
     pub commit_sha: String,
     pub commit_date: String,
-    pub pr_number: Option<u64>,
+    /// PR that actually deleted the test (the sub-PR for rollups)
+    pub origin_pr: Option<u64>,
+    /// Rollup PR the deletion landed through, if any
+    pub rollup_pr: Option<u64>,
+    /// Commit message format the mainline PR reference was recognized from
+    pub pr_format: Option<PrRecognizer>,
+    pub kind: DeletionKind,
+}
+
+/// A crash test file currently present in `tests/crashes/`
+#[derive(Debug, Clone)]
+pub struct CrashTestFile {
+    pub file_name: String,
+    /// Path relative to the repository root
+    pub file_path: String,
+    /// Name of the watch group the file belongs to
+    pub group: String,
+    pub issues: Vec<IssueRef>,
+}
+
+/// An issue referenced by a crash test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssueRef {
+    pub number: u64,
+    pub source: IssueSource,
+}
+
+/// Where an issue reference came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSource {
+    /// Leading digits of the filename (e.g. `12345-foo.rs`)
+    Filename,
+    /// A `//@ known-bug: #12345` directive in the test header
+    Directive,
+    /// Both the filename and a directive agree
+    Both,
+}
+
+/// Issue numbers referenced by a crash test, regardless of source
+pub fn issue_numbers(issues: &[IssueRef]) -> impl Iterator<Item = u64> + '_ {
+    issues.iter().map(|issue| issue.number)
+}
+
+/// Whether the filename and the `known-bug` directives disagree
+pub fn has_issue_mismatch(issues: &[IssueRef]) -> bool {
+    issues.iter().any(|i| i.source == IssueSource::Filename)
+        && issues.iter().any(|i| i.source == IssueSource::Directive)
+}
+
+/// How a crash test left `tests/crashes/`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeletionKind {
+    /// The file was removed from the tree outright
+    Deleted,
+    /// The file was renamed or copied elsewhere (typically promoted to a ui test)
+    Moved {
+        new_path: String,
+        /// Similarity score reported by git rename detection (0-100)
+        similarity: u16,
+    },
+}
+
+impl DeletedCrashTest {
+    /// PR to attribute the deletion to: the origin PR if known, else the rollup
+    pub fn responsible_pr(&self) -> Option<u64> {
+        self.origin_pr.or(self.rollup_pr)
+    }
+
+    /// Whether the test was moved elsewhere rather than deleted outright
+    pub fn is_moved(&self) -> bool {
+        matches!(self.kind, DeletionKind::Moved { .. })
+    }
 }
 
 /// Scan git history for deleted crash test files
+/// Reuses a checkpoint from a previous run so only new commits are walked.
+/// If the checkpoint is unusable (history rewritten, older start date,
+/// different PR formats or repository) the whole range is rescanned. The returned
+/// checkpoint has no end-date filtering; use `filter_by_date` for that.
 pub fn scan_deleted_crash_tests(
     repo_path: &Path,
+    checkpoint: Option<ScanCheckpoint>,
     from_date: Option<NaiveDate>,
-    to_date: Option<NaiveDate>,
-) -> Result<Vec<DeletedCrashTest>> {
+    options: &ScanOptions,
+) -> Result<ScanCheckpoint> {
     let repo = Repository::open(repo_path)
         .context("Failed to open git repository")?;
 
+    let head = repo.head()
+        .context("Failed to get HEAD")?
+        .peel_to_commit()
+        .context("Failed to resolve HEAD commit")?
+        .id();
+
+    let pr_formats: Vec<String> = options
+        .pr_extractor
+        .recognizers()
+        .iter()
+        .map(|recognizer| recognizer.name())
+        .collect();
+
+    let key = scan_key(options.groups);
+    let usable = checkpoint.filter(|checkpoint| {
+        let reason = checkpoint_unusable_reason(
+            &repo,
+            checkpoint,
+            &key,
+            options.github_repo,
+            head,
+            from_date,
+            &pr_formats,
+        );
+        if let Some(reason) = &reason {
+            options.progress.message(format!("Scan checkpoint not usable ({}), rescanning history", reason));
+        }
+        reason.is_none()
+    });
+
+    let (deleted_files, from) = match usable {
+        Some(checkpoint) => {
+            let checkpoint_head = Oid::from_str(&checkpoint.head)
+                .context("Invalid checkpoint commit")?;
+
+            options.progress.detail(format!(
+                "Resuming scan from checkpoint {} ({} deletions cached)",
+                &checkpoint.head[..8],
+                checkpoint.deleted_files.len()
+            ));
+
+            // New commits are always newer than the checkpoint, so no date cutoff
+            let mut deleted_files =
+                scan_commits(&repo, Some(checkpoint_head), None, options)?;
+            deleted_files.extend(checkpoint.deleted_files);
+            (deleted_files, checkpoint.from)
+        }
+        None => (
+            scan_commits(&repo, None, from_date, options)?,
+            from_date,
+        ),
+    };
+
+    Ok(ScanCheckpoint {
+        repo_path: repo_path.canonicalize().unwrap_or_else(|_| repo_path.to_path_buf()),
+        pathspec: key,
+        repository: Some(options.github_repo.clone()),
+        pr_formats,
+        head: head.to_string(),
+        from,
+        timestamp: chrono::Utc::now(),
+        deleted_files,
+    })
+}
+
+/// Explain why a checkpoint can't be reused for this scan, if it can't
+fn checkpoint_unusable_reason(
+    repo: &Repository,
+    checkpoint: &ScanCheckpoint,
+    scan_key: &str,
+    github_repo: &GitHubRepo,
+    head: Oid,
+    from_date: Option<NaiveDate>,
+    pr_formats: &[String],
+) -> Option<String> {
+    if checkpoint.pathspec != scan_key {
+        return Some("different watch groups".to_string());
+    }
+    // Directives naming another repository's issues are dropped while scanning
+    if checkpoint.repository.as_ref() != Some(github_repo) {
+        return Some("different repository".to_string());
+    }
+    if checkpoint.pr_formats != pr_formats {
+        return Some("different PR formats".to_string());
+    }
+
+    // The checkpoint must cover at least the requested date range
+    match (checkpoint.from, from_date) {
+        (None, _) => {}
+        (Some(_), None) => return Some("checkpoint doesn't cover full history".to_string()),
+        (Some(covered), Some(requested)) if requested < covered => {
+            return Some(format!("checkpoint only covers history since {}", covered));
+        }
+        _ => {}
+    }
+
+    // After a force-push or rebase the checkpoint may no longer be in HEAD's history
+    let Ok(checkpoint_head) = Oid::from_str(&checkpoint.head) else {
+        return Some("invalid checkpoint commit".to_string());
+    };
+    if checkpoint_head != head && !repo.graph_descendant_of(head, checkpoint_head).unwrap_or(false) {
+        return Some("checkpoint commit is no longer an ancestor of HEAD".to_string());
+    }
+
+    None
+}
+
+/// Keep only deletions whose commit date falls within the given range
+pub fn filter_by_date(
+    deleted_files: Vec<DeletedCrashTest>,
+    from_date: Option<NaiveDate>,
+    to_date: Option<NaiveDate>,
+) -> Vec<DeletedCrashTest> {
+    deleted_files
+        .into_iter()
+        .filter(|file| {
+            let Ok(date) = file.commit_date.parse::<NaiveDate>() else {
+                return true;
+            };
+            from_date.is_none_or(|from| date >= from) && to_date.is_none_or(|to| date <= to)
+        })
+        .collect()
+}
+
+/// Walk first-parent history from HEAD collecting deleted crash tests
+/// Stops at `hide` (exclusive) or once commits are older than `from_date`.
+/// Commits are diffed on `options.jobs` worker threads; results are in commit order.
+fn scan_commits(
+    repo: &Repository,
+    hide: Option<Oid>,
+    from_date: Option<NaiveDate>,
+    options: &ScanOptions,
+) -> Result<Vec<DeletedCrashTest>> {
+    let commits = collect_first_parent_commits(repo, hide, from_date)?;
+    let scanned = AtomicUsize::new(0);
+
+    let deleted_files = if options.jobs <= 1 || commits.len() < 2 {
+        scan_commit_list(repo, &commits, options, &scanned)?
+    } else {
+        scan_commits_parallel(repo.path(), &commits, options, &scanned)?
+    };
+
+    // Replace the running count
+    let commits_scanned = scanned.load(Ordering::Relaxed);
+    if commits_scanned >= 1000 {
+        options.progress.message(format!("  Scanned {} commits total", commits_scanned));
+    }
+
+    Ok(deleted_files)
+}
+
+/// List first-parent commits from HEAD, newest first
+fn collect_first_parent_commits(
+    repo: &Repository,
+    hide: Option<Oid>,
+    from_date: Option<NaiveDate>,
+) -> Result<Vec<(Oid, NaiveDate)>> {
     let mut revwalk = repo.revwalk()
         .context("Failed to create revwalk")?;
 
@@ -34,25 +322,20 @@ pub fn scan_deleted_crash_tests(
     revwalk.push_head()
         .context("Failed to push HEAD")?;
 
-    let mut deleted_files = Vec::new();
-    let mut commits_scanned = 0;
+    // Stop at commits that were already scanned
+    if let Some(hide) = hide {
+        revwalk.hide(hide)
+            .context("Failed to hide checkpoint commit")?;
+    }
 
-    // Walk through commits
+    let mut commits = Vec::new();
     for oid in revwalk {
         let oid = oid.context("Failed to get commit OID")?;
         let commit = repo.find_commit(oid)
             .context("Failed to find commit")?;
 
-        commits_scanned += 1;
-
-        // Progress indicator every 1000 commits
-        if commits_scanned % 1000 == 0 {
-            eprint!("\r  Scanned {} commits...", commits_scanned);
-        }
-
         // Get commit timestamp
-        let commit_time = commit.time();
-        let commit_timestamp = commit_time.seconds();
+        let commit_timestamp = commit.time().seconds();
         let commit_date = chrono::DateTime::from_timestamp(commit_timestamp, 0)
             .context("Invalid timestamp")?
             .date_naive();
@@ -65,95 +348,466 @@ pub fn scan_deleted_crash_tests(
             }
         }
 
-        if let Some(to) = to_date {
-            if commit_date > to {
-                // Haven't reached the to_date yet, skip this commit
-                continue;
-            }
+        commits.push((oid, commit_date));
+    }
+
+    Ok(commits)
+}
+
+/// Split the commit list into chunks and diff them on a pool of worker threads
+/// Each worker opens its own repository handle, since `Repository` isn't `Sync`
+fn scan_commits_parallel(
+    git_dir: &Path,
+    commits: &[(Oid, NaiveDate)],
+    options: &ScanOptions,
+    scanned: &AtomicUsize,
+) -> Result<Vec<DeletedCrashTest>> {
+    let jobs = options.jobs;
+    // Several chunks per worker so a slow stretch of history doesn't leave
+    // the other workers idle
+    let chunk_size = commits.len().div_ceil(jobs * CHUNKS_PER_JOB).max(1);
+    let chunks: Vec<&[(Oid, NaiveDate)]> = commits.chunks(chunk_size).collect();
+    let next_chunk = AtomicUsize::new(0);
+
+    let mut chunk_results = std::thread::scope(|scope| -> Result<Vec<(usize, Vec<DeletedCrashTest>)>> {
+        let workers: Vec<_> = (0..jobs.min(chunks.len()))
+            .map(|_| {
+                scope.spawn(|| -> Result<Vec<(usize, Vec<DeletedCrashTest>)>> {
+                    let repo = Repository::open(git_dir)
+                        .context("Failed to open git repository in scan worker")?;
+
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next_chunk.fetch_add(1, Ordering::Relaxed);
+                        let Some(chunk) = chunks.get(index) else {
+                            break;
+                        };
+                        results.push((index, scan_commit_list(&repo, chunk, options, scanned)?));
+                    }
+
+                    Ok(results)
+                })
+            })
+            .collect();
+
+        let mut all_results = Vec::new();
+        for worker in workers {
+            let results = worker
+                .join()
+                .map_err(|_| anyhow::anyhow!("Scan worker thread panicked"))??;
+            all_results.extend(results);
         }
 
-        // Get parent commit (if exists)
-        if commit.parent_count() == 0 {
-            continue; // Skip initial commit
+        Ok(all_results)
+    })?;
+
+    // Merge back in commit order
+    chunk_results.sort_by_key(|(index, _)| *index);
+
+    Ok(chunk_results
+        .into_iter()
+        .flat_map(|(_, deleted_files)| deleted_files)
+        .collect())
+}
+
+/// Diff each commit in the list against its first parent
+fn scan_commit_list(
+    repo: &Repository,
+    commits: &[(Oid, NaiveDate)],
+    options: &ScanOptions,
+    scanned: &AtomicUsize,
+) -> Result<Vec<DeletedCrashTest>> {
+    let mut deleted_files = Vec::new();
+
+    for &(oid, commit_date) in commits {
+        let commits_scanned = scanned.fetch_add(1, Ordering::Relaxed) + 1;
+
+        // Progress indicator every 1000 commits
+        if commits_scanned.is_multiple_of(1000) {
+            options.progress.count(format!("  Scanned {} commits...", commits_scanned));
         }
 
-        let parent = commit.parent(0)
-            .context("Failed to get parent commit")?;
+        deleted_files.extend(scan_commit(repo, oid, commit_date, options)?);
+    }
 
-        let tree = commit.tree()
-            .context("Failed to get commit tree")?;
-        let parent_tree = parent.tree()
-            .context("Failed to get parent tree")?;
+    Ok(deleted_files)
+}
+
+/// Find crash tests deleted by a single commit
+fn scan_commit(
+    repo: &Repository,
+    oid: Oid,
+    commit_date: NaiveDate,
+    options: &ScanOptions,
+) -> Result<Vec<DeletedCrashTest>> {
+    let mut deleted_files = Vec::new();
+
+    let commit = repo.find_commit(oid)
+        .context("Failed to find commit")?;
+
+    // Get parent commit (if exists)
+    if commit.parent_count() == 0 {
+        return Ok(deleted_files); // Skip initial commit
+    }
+
+    let parent = commit.parent(0)
+        .context("Failed to get parent commit")?;
+
+    let tree = commit.tree()
+        .context("Failed to get commit tree")?;
+    let parent_tree = parent.tree()
+        .context("Failed to get parent tree")?;
+
+    // Create diff between parent and current commit
+    // Optimization: Only diff files in the watched directories
+    let diff = diff_watched(repo, &parent_tree, &tree, options.groups)?;
+
+    // Look for deleted files in the watched directories
+    let deleted_paths: Vec<String> = diff
+        .deltas()
+        .filter(|delta| delta.status() == Delta::Deleted)
+        .filter_map(|delta| delta.old_file().path().map(|p| p.to_string_lossy().to_string()))
+        .filter(|path| group_for_path(options.groups, path).is_some())
+        .collect();
+
+    if deleted_paths.is_empty() {
+        return Ok(deleted_files);
+    }
+
+    // Only pay for whole-tree rename detection on commits that actually
+    // removed a crash test
+    let moves = find_moves(repo, &parent_tree, &tree, options.groups)?;
+
+    // Extract PR number from commit message
+    let commit_message = commit.message().unwrap_or("");
+    let pr_ref = options.pr_extractor.extract(commit_message);
+    let pr_number = pr_ref.map(|pr| pr.number);
+
+    // For rollups, find the sub-PR that actually touched each file
+    let rollup_origins = if is_rollup_message(commit_message) && commit.parent_count() > 1 {
+        find_rollup_origins(repo, &commit, &deleted_paths, options.groups)?
+    } else {
+        HashMap::new()
+    };
+
+    for path_str in deleted_paths {
+        let Some(group) = group_for_path(options.groups, &path_str) else {
+            continue;
+        };
+
+        let kind = match moves.get(&path_str) {
+            // Renamed within a watched directory - the test still exists
+            Some((new_path, _)) if group_for_path(options.groups, new_path).is_some() => continue,
+            Some((new_path, similarity)) => DeletionKind::Moved {
+                new_path: new_path.clone(),
+                similarity: *similarity,
+            },
+            None => DeletionKind::Deleted,
+        };
+
+        // Extract issue numbers from the filename and the header as it
+        // was just before the deletion
+        let content = read_blob(repo, &parent_tree, &path_str);
+        let issues = resolve_issues(&path_str, content.as_deref(), group.issue, options.github_repo);
+        if issues.is_empty() {
+            continue;
+        }
+
+        let (origin_pr, rollup_pr) = if is_rollup_message(commit_message) {
+            (rollup_origins.get(&path_str).copied(), pr_number)
+        } else {
+            (pr_number, None)
+        };
+
+        deleted_files.push(DeletedCrashTest {
+            file_path: path_str,
+            group: group.name.clone(),
+            issues,
+            commit_sha: commit.id().to_string(),
+            commit_date: commit_date.to_string(),
+            origin_pr,
+            rollup_pr,
+            pr_format: pr_ref.map(|pr| pr.recognizer),
+            kind,
+        });
+    }
+
+    Ok(deleted_files)
+}
+
+/// Find which PR inside a rollup removed each of the given paths
+/// Walks the second-parent side of the bors merge looking for
+/// "Rollup merge of #N" commits that deleted one of the paths
+fn find_rollup_origins(
+    repo: &Repository,
+    merge: &Commit,
+    paths: &[String],
+    groups: &[WatchGroup],
+) -> Result<HashMap<String, u64>> {
+    let mut origins = HashMap::new();
+
+    let mut revwalk = repo.revwalk()
+        .context("Failed to create rollup revwalk")?;
+    revwalk.push(merge.parent_id(1).context("Failed to get rollup parent")?)
+        .context("Failed to push rollup parent")?;
+    revwalk.hide(merge.parent_id(0).context("Failed to get mainline parent")?)
+        .context("Failed to hide mainline parent")?;
+
+    for oid in revwalk {
+        let oid = oid.context("Failed to get rollup commit OID")?;
+        let commit = repo.find_commit(oid)
+            .context("Failed to find rollup commit")?;
+
+        let Some(sub_pr) = PrRecognizer::RollupMerge.recognize(commit.message().unwrap_or("")) else {
+            continue;
+        };
+        if commit.parent_count() == 0 {
+            continue;
+        }
 
-        // Create diff between parent and current commit
-        // Optimization: Only diff files in tests/crashes/ directory
-        let mut diff_opts = DiffOptions::new();
-        diff_opts.pathspec("tests/crashes/*.rs");
+        // A rollup merge's first parent is the previous state of the rollup
+        // branch, so diffing against it isolates that sub-PR's changes
+        let tree = commit.tree()
+            .context("Failed to get rollup commit tree")?;
+        let parent_tree = commit.parent(0)
+            .context("Failed to get rollup commit parent")?
+            .tree()
+            .context("Failed to get rollup parent tree")?;
 
-        let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut diff_opts))
-            .context("Failed to create diff")?;
+        let diff = diff_watched(repo, &parent_tree, &tree, groups)?;
 
-        // Look for deleted files in tests/crashes/
         for delta in diff.deltas() {
-            if delta.status() == Delta::Deleted {
-                if let Some(old_file) = delta.old_file().path() {
-                    let path_str = old_file.to_string_lossy();
-
-                    // Extract issue number from filename
-                    if let Some(issue_number) = extract_issue_number(&path_str) {
-                        // Extract PR number from commit message
-                        let commit_message = commit.message().unwrap_or("");
-                        let pr_number = extract_pr_number(commit_message);
-
-                        deleted_files.push(DeletedCrashTest {
-                            file_path: path_str.to_string(),
-                            issue_number,
-                            commit_sha: commit.id().to_string(),
-                            commit_date: commit_date.to_string(),
-                            pr_number,
-                        });
-                    }
+            if delta.status() != Delta::Deleted {
+                continue;
+            }
+            if let Some(old_file) = delta.old_file().path() {
+                let path_str = old_file.to_string_lossy().to_string();
+                if paths.contains(&path_str) {
+                    origins.entry(path_str).or_insert(sub_pr);
                 }
             }
         }
+
+        if origins.len() == paths.len() {
+            break;
+        }
     }
 
-    // Clear progress line
-    if commits_scanned >= 1000 {
-        eprintln!("\r  Scanned {} commits total", commits_scanned);
+    Ok(origins)
+}
+
+/// Diff two trees, limited to the watched directories
+fn diff_watched<'repo>(
+    repo: &'repo Repository,
+    parent_tree: &Tree,
+    tree: &Tree,
+    groups: &[WatchGroup],
+) -> Result<Diff<'repo>> {
+    let mut diff_opts = DiffOptions::new();
+    for group in groups {
+        diff_opts.pathspec(group.pathspec());
     }
 
-    Ok(deleted_files)
+    repo.diff_tree_to_tree(Some(parent_tree), Some(tree), Some(&mut diff_opts))
+        .context("Failed to create diff")
 }
 
-/// Get current crash test files in the repository
-/// Returns a HashSet of filenames (without path) that currently exist
-pub fn get_current_crash_test_files(repo_path: &Path) -> Result<HashSet<String>> {
-    let crashes_dir = repo_path.join("tests/crashes");
-    let mut current_files = HashSet::new();
+/// Read a file's content from a tree, if it exists and is valid UTF-8
+fn read_blob(repo: &Repository, tree: &Tree, path: &str) -> Option<String> {
+    let entry = tree.get_path(Path::new(path)).ok()?;
+    let blob = repo.find_blob(entry.id()).ok()?;
+    std::str::from_utf8(blob.content()).ok().map(str::to_string)
+}
 
-    if !crashes_dir.exists() {
-        return Ok(current_files);
+/// Combine filename and `known-bug` directive issue numbers for a crash test
+pub fn resolve_issues(
+    path: &str,
+    content: Option<&str>,
+    rule: IssueRule,
+    github_repo: &GitHubRepo,
+) -> Vec<IssueRef> {
+    let from_filename = extract_issue_number(path, rule);
+    let from_directives = content
+        .map(|content| {
+            header::parse_known_bugs(content, CommentSyntax::for_path(path), &github_repo.to_string())
+        })
+        .unwrap_or_default();
+
+    let mut issues = Vec::new();
+    if let Some(number) = from_filename {
+        let source = if from_directives.contains(&number) {
+            IssueSource::Both
+        } else {
+            IssueSource::Filename
+        };
+        issues.push(IssueRef { number, source });
     }
+    for number in from_directives {
+        if Some(number) != from_filename {
+            issues.push(IssueRef {
+                number,
+                source: IssueSource::Directive,
+            });
+        }
+    }
+
+    issues
+}
 
-    for entry in std::fs::read_dir(&crashes_dir)
-        .context("Failed to read tests/crashes directory")?
-    {
-        let entry = entry.context("Failed to read directory entry")?;
-        let path = entry.path();
+/// Run rename/copy detection over the whole tree between two commits
+/// Returns a map of old path -> (new path, similarity) for crash tests that moved
+fn find_moves(
+    repo: &Repository,
+    parent_tree: &Tree,
+    tree: &Tree,
+    groups: &[WatchGroup],
+) -> Result<HashMap<String, (String, u16)>> {
+    let mut diff = repo
+        .diff_tree_to_tree(Some(parent_tree), Some(tree), None)
+        .context("Failed to create full-tree diff")?;
 
-        if path.is_file() {
-            if let Some(filename) = path.file_name().and_then(|f| f.to_str()) {
-                if filename.ends_with(".rs") {
-                    current_files.insert(filename.to_string());
-                }
+    let mut find_opts = DiffFindOptions::new();
+    find_opts.renames(true).copies(true);
+    diff.find_similar(Some(&mut find_opts))
+        .context("Failed to run rename detection")?;
+
+    collect_moves(&diff, groups)
+}
+
+/// Collect renamed/copied crash tests from a diff that already went through `find_similar`
+fn collect_moves(diff: &Diff, groups: &[WatchGroup]) -> Result<HashMap<String, (String, u16)>> {
+    let mut moves: HashMap<String, (String, u16, Delta)> = HashMap::new();
+
+    // git2 doesn't expose the delta similarity directly, but the raw format
+    // carries it in the status column (e.g. "R087")
+    diff.print(DiffFormat::Raw, |delta, _hunk, line| {
+        if !matches!(delta.status(), Delta::Renamed | Delta::Copied) {
+            return true;
+        }
+
+        let (Some(old_path), Some(new_path)) = (delta.old_file().path(), delta.new_file().path())
+        else {
+            return true;
+        };
+
+        let old_path = old_path.to_string_lossy().to_string();
+        if group_for_path(groups, &old_path).is_none() {
+            return true;
+        }
+
+        let similarity = parse_raw_similarity(&String::from_utf8_lossy(line.content()))
+            .unwrap_or(0);
+
+        // Prefer a rename over a copy if git reports both for the same source
+        let replace = match moves.get(&old_path) {
+            Some((_, _, existing)) => *existing == Delta::Copied && delta.status() == Delta::Renamed,
+            None => true,
+        };
+        if replace {
+            moves.insert(
+                old_path,
+                (new_path.to_string_lossy().to_string(), similarity, delta.status()),
+            );
+        }
+
+        true
+    })
+    .context("Failed to read rename information")?;
+
+    Ok(moves
+        .into_iter()
+        .map(|(old, (new, similarity, _))| (old, (new, similarity)))
+        .collect())
+}
+
+/// Parse the similarity score out of a `git diff --raw` line
+/// Example: ":100644 100644 abc1234 def5678 R087\told\tnew" -> Some(87)
+fn parse_raw_similarity(line: &str) -> Option<u16> {
+    let status = line.split('\t').next()?.split_whitespace().last()?;
+    let score = status.strip_prefix('R').or_else(|| status.strip_prefix('C'))?;
+    score.parse().ok()
+}
+
+/// Get current crash test files in the repository
+/// Issue numbers come from the filename and the header in the worktree
+pub fn get_current_crash_test_files(
+    repo_path: &Path,
+    groups: &[WatchGroup],
+    github_repo: &GitHubRepo,
+) -> Result<Vec<CrashTestFile>> {
+    let mut current_files = Vec::new();
+
+    for group in groups {
+        let group_dir = repo_path.join(&group.dir);
+        if !group_dir.exists() {
+            continue;
+        }
+
+        for entry in std::fs::read_dir(&group_dir)
+            .with_context(|| format!("Failed to read {} directory", group.dir))?
+        {
+            let entry = entry.context("Failed to read directory entry")?;
+            let path = entry.path();
+
+            if !path.is_file() {
+                continue;
             }
+            let Some(filename) = path.file_name().and_then(|f| f.to_str()) else {
+                continue;
+            };
+            if !matches_glob(&group.glob, Path::new(filename)) {
+                continue;
+            }
+
+            let content = std::fs::read_to_string(&path).ok();
+            current_files.push(CrashTestFile {
+                file_name: filename.to_string(),
+                file_path: format!("{}/{}", group.dir.trim_end_matches('/'), filename),
+                group: group.name.clone(),
+                issues: resolve_issues(filename, content.as_deref(), group.issue, github_repo),
+            });
         }
     }
 
+    current_files.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
     Ok(current_files)
 }
 
+/// Get the URL of the `origin` remote, if the repository has one
+pub fn origin_url(repo_path: &Path) -> Result<Option<String>> {
+    let repo = Repository::open(repo_path)
+        .context("Failed to open git repository")?;
+
+    let Ok(remote) = repo.find_remote("origin") else {
+        return Ok(None);
+    };
+
+    Ok(remote.url().map(str::to_string))
+}
+
+/// Look up the PR that a commit belongs to in the local repository
+/// Returns None if the commit isn't known locally or its message has no PR reference
+pub fn find_pr_for_commit(
+    repo_path: &Path,
+    sha: &str,
+    pr_extractor: &PrExtractor,
+) -> Result<Option<u64>> {
+    let repo = Repository::open(repo_path)
+        .context("Failed to open git repository")?;
+
+    let Ok(oid) = Oid::from_str(sha) else {
+        return Ok(None);
+    };
+    let Ok(commit) = repo.find_commit(oid) else {
+        return Ok(None);
+    };
+
+    Ok(pr_extractor
+        .extract(commit.message().unwrap_or(""))
+        .map(|pr| pr.number))
+}
+
 /// Extract issue number from crash test filename or path
 /// Examples:
 /// - "tests/crashes/12345.rs" -> Some(12345)
@@ -180,64 +834,313 @@ pub fn extract_issue_number_from_filename(path: &str) -> Option<u64> {
     None
 }
 
-/// Extract issue number from crash test filename (internal use)
-fn extract_issue_number(path: &str) -> Option<u64> {
-    extract_issue_number_from_filename(path)
-}
-
-/// Extract PR number from commit message
-/// Rust bors commits follow the pattern: "Auto merge of #12345 - ..."
-/// Examples:
-/// - "Auto merge of #147900 - Zalathar:rollup-ril6jsi, r=Zalathar" -> Some(147900)
-/// - "Regular commit message" -> None
-fn extract_pr_number(message: &str) -> Option<u64> {
-    // Look for "Auto merge of #" followed by digits
-    let prefix = "Auto merge of #";
-    if let Some(start) = message.find(prefix) {
-        let number_start = start + prefix.len();
-        let rest = &message[number_start..];
-
-        // Extract digits until we hit a non-digit character
-        let number_str: String = rest.chars()
-            .take_while(|c| c.is_ascii_digit())
-            .collect();
-
-        return number_str.parse::<u64>().ok();
+/// Extract issue number from crash test filename according to a group's rule
+fn extract_issue_number(path: &str, rule: IssueRule) -> Option<u64> {
+    match rule {
+        IssueRule::LeadingNumber => extract_issue_number_from_filename(path),
+        IssueRule::AnyNumber => {
+            // First run of digits anywhere in the stem (e.g. "ice-12345" -> 12345)
+            let filename = Path::new(path).file_stem()?.to_str()?;
+            let digits: String = filename
+                .chars()
+                .skip_while(|c| !c.is_ascii_digit())
+                .take_while(|c| c.is_ascii_digit())
+                .collect();
+            digits.parse().ok()
+        }
+        IssueRule::DirectiveOnly => None,
     }
+}
 
-    None
+/// Check whether a bors merge commit is a rollup
+/// Rollup messages contain a "Rollup of N pull requests" line
+fn is_rollup_message(message: &str) -> bool {
+    message.lines().any(|line| {
+        let line = line.trim();
+        line.starts_with("Rollup of ") && line.ends_with("pull requests")
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Config;
+    use std::sync::LazyLock;
 
     #[test]
     fn test_extract_issue_number() {
-        assert_eq!(extract_issue_number("tests/crashes/12345.rs"), Some(12345));
-        assert_eq!(extract_issue_number("tests/crashes/12345-foo.rs"), Some(12345));
-        assert_eq!(extract_issue_number("tests/crashes/98765-bar-baz.rs"), Some(98765));
-        assert_eq!(extract_issue_number("tests/crashes/foo.rs"), None);
-        assert_eq!(extract_issue_number("tests/crashes/foo-12345.rs"), None);
+        let leading = IssueRule::LeadingNumber;
+        assert_eq!(extract_issue_number("tests/crashes/12345.rs", leading), Some(12345));
+        assert_eq!(extract_issue_number("tests/crashes/12345-foo.rs", leading), Some(12345));
+        assert_eq!(extract_issue_number("tests/crashes/98765-bar-baz.rs", leading), Some(98765));
+        assert_eq!(extract_issue_number("tests/crashes/foo.rs", leading), None);
+        assert_eq!(extract_issue_number("tests/crashes/foo-12345.rs", leading), None);
+
+        let any = IssueRule::AnyNumber;
+        assert_eq!(extract_issue_number("tests/ui/crashes/ice-12345.rs", any), Some(12345));
+        assert_eq!(extract_issue_number("tests/ui/crashes/ice-12345-2.rs", any), Some(12345));
+        assert_eq!(extract_issue_number("tests/ui/crashes/12345.sh", any), Some(12345));
+        assert_eq!(extract_issue_number("tests/ui/crashes/foo.rs", any), None);
+
+        assert_eq!(extract_issue_number("tests/crashes/12345.rs", IssueRule::DirectiveOnly), None);
     }
 
     #[test]
-    fn test_extract_pr_number() {
+    fn test_resolve_issues() {
+        let github_repo = GitHubRepo::default();
+        let resolve = |path: &str, content: Option<&str>| {
+            resolve_issues(path, content, IssueRule::LeadingNumber, &github_repo)
+        };
+
         assert_eq!(
-            extract_pr_number("Auto merge of #147900 - Zalathar:rollup-ril6jsi, r=Zalathar"),
-            Some(147900)
+            resolve("tests/crashes/12345.rs", Some("//@ known-bug: #12345\n")),
+            vec![IssueRef { number: 12345, source: IssueSource::Both }]
         );
         assert_eq!(
-            extract_pr_number("Auto merge of #12345 - username:branch, r=reviewer"),
-            Some(12345)
+            resolve("tests/crashes/foo.rs", Some("//@ known-bug: #12345\n")),
+            vec![IssueRef { number: 12345, source: IssueSource::Directive }]
         );
         assert_eq!(
-            extract_pr_number("Regular commit message without PR"),
-            None
+            resolve("tests/crashes/12345.rs", None),
+            vec![IssueRef { number: 12345, source: IssueSource::Filename }]
         );
+
+        let mismatched = resolve("tests/crashes/111.rs", Some("//@ known-bug: #222\n"));
+        assert_eq!(
+            mismatched,
+            vec![
+                IssueRef { number: 111, source: IssueSource::Filename },
+                IssueRef { number: 222, source: IssueSource::Directive },
+            ]
+        );
+        assert!(has_issue_mismatch(&mismatched));
+        assert!(!has_issue_mismatch(&resolve("tests/crashes/foo.rs", None)));
+    }
+
+    #[test]
+    fn test_parse_raw_similarity() {
         assert_eq!(
-            extract_pr_number("Mention #12345 but not auto merge"),
+            parse_raw_similarity(":100644 100644 abc1234 def5678 R087\ttests/crashes/1.rs\ttests/ui/1.rs\n"),
+            Some(87)
+        );
+        assert_eq!(
+            parse_raw_similarity(":100644 100644 abc1234 def5678 C100\ta.rs\tb.rs\n"),
+            Some(100)
+        );
+        assert_eq!(
+            parse_raw_similarity(":100644 000000 abc1234 0000000 D\ttests/crashes/1.rs\n"),
             None
         );
     }
+
+    /// Commit a set of file changes (None = delete) on top of HEAD
+    fn commit(repo: &Repository, message: &str, changes: &[(&str, Option<&str>)]) -> Oid {
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        let mut index = repo.index().unwrap();
+        for (path, content) in changes {
+            let full_path = workdir.join(path);
+            match content {
+                Some(content) => {
+                    std::fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+                    std::fs::write(&full_path, content).unwrap();
+                    index.add_path(Path::new(path)).unwrap();
+                }
+                None => {
+                    std::fs::remove_file(&full_path).unwrap();
+                    index.remove_path(Path::new(path)).unwrap();
+                }
+            }
+        }
+        index.write().unwrap();
+
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+        let parents: Vec<&Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+            .unwrap()
+    }
+
+    static QUIET: LazyLock<Progress> = LazyLock::new(Progress::default);
+
+    fn scan_options<'a>(
+        groups: &'a [WatchGroup],
+        pr_extractor: &'a PrExtractor,
+        github_repo: &'a GitHubRepo,
+        jobs: usize,
+    ) -> ScanOptions<'a> {
+        ScanOptions {
+            groups,
+            pr_extractor,
+            github_repo,
+            jobs,
+            progress: &QUIET,
+        }
+    }
+
+    fn summary(files: &[DeletedCrashTest]) -> Vec<(String, String, Option<u64>)> {
+        files
+            .iter()
+            .map(|f| (f.file_path.clone(), f.commit_sha.clone(), f.origin_pr))
+            .collect()
+    }
+
+    #[test]
+    fn test_incremental_scan_matches_full_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let extractor = PrExtractor::default();
+        let github_repo = GitHubRepo::default();
+        let groups = Config::default().watch;
+        let options = scan_options(&groups, &extractor, &github_repo, 1);
+
+        commit(&repo, "Initial", &[
+            ("tests/crashes/1.rs", Some("fn main() {}\n")),
+            ("tests/crashes/2.rs", Some("fn main() {}\n")),
+            ("tests/crashes/3.rs", Some("fn main() {}\n")),
+        ]);
+        commit(&repo, "Auto merge of #10 - a:b, r=c", &[("tests/crashes/1.rs", None)]);
+
+        let checkpoint = scan_deleted_crash_tests(dir.path(), None, None, &options).unwrap();
+        assert_eq!(checkpoint.deleted_files.len(), 1);
+
+        commit(&repo, "Auto merge of #11 - a:b, r=c", &[("tests/crashes/2.rs", None)]);
+        commit(&repo, "Auto merge of #12 - a:b, r=c", &[("tests/crashes/3.rs", None)]);
+
+        let incremental =
+            scan_deleted_crash_tests(dir.path(), Some(checkpoint), None, &options).unwrap();
+        let full = scan_deleted_crash_tests(dir.path(), None, None, &options).unwrap();
+
+        assert_eq!(summary(&incremental.deleted_files), summary(&full.deleted_files));
+        assert_eq!(incremental.head, full.head);
+        assert_eq!(
+            incremental.deleted_files.iter().map(|f| f.origin_pr).collect::<Vec<_>>(),
+            vec![Some(12), Some(11), Some(10)]
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_not_ancestor_of_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let extractor = PrExtractor::default();
+        let github_repo = GitHubRepo::default();
+        let groups = Config::default().watch;
+        let options = scan_options(&groups, &extractor, &github_repo, 1);
+
+        let base = commit(&repo, "Initial", &[
+            ("tests/crashes/1.rs", Some("fn main() {}\n")),
+            ("tests/crashes/2.rs", Some("fn main() {}\n")),
+        ]);
+        commit(&repo, "Auto merge of #10 - a:b, r=c", &[("tests/crashes/1.rs", None)]);
+        let checkpoint = scan_deleted_crash_tests(dir.path(), None, None, &options).unwrap();
+
+        // Simulate a force-push: rewind to the base commit and build different history
+        let base_commit = repo.find_commit(base).unwrap();
+        repo.reset(base_commit.as_object(), git2::ResetType::Hard, None).unwrap();
+        commit(&repo, "Auto merge of #20 - a:b, r=c", &[("tests/crashes/2.rs", None)]);
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap().id();
+        let pr_formats: Vec<String> = extractor.recognizers().iter().map(|r| r.name()).collect();
+        let key = scan_key(&groups);
+        assert!(
+            checkpoint_unusable_reason(&repo, &checkpoint, &key, &github_repo, head, None, &pr_formats)
+                .is_some()
+        );
+
+        let rescanned =
+            scan_deleted_crash_tests(dir.path(), Some(checkpoint), None, &options).unwrap();
+        assert_eq!(
+            summary(&rescanned.deleted_files)
+                .into_iter()
+                .map(|(path, _, pr)| (path, pr))
+                .collect::<Vec<_>>(),
+            vec![("tests/crashes/2.rs".to_string(), Some(20))]
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_different_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let extractor = PrExtractor::default();
+        let groups = Config::default().watch;
+        let upstream = GitHubRepo::default();
+        let fork = GitHubRepo::new("myfork", "rust");
+
+        commit(&repo, "Initial", &[("tests/crashes/1.rs", Some("//@ known-bug: myfork/rust#5\nfn main() {}\n"))]);
+        commit(&repo, "Auto merge of #10 - a:b, r=c", &[("tests/crashes/1.rs", None)]);
+        let checkpoint =
+            scan_deleted_crash_tests(dir.path(), None, None, &scan_options(&groups, &extractor, &upstream, 1))
+                .unwrap();
+        assert_eq!(checkpoint.repository, Some(upstream.clone()));
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap().id();
+        let pr_formats: Vec<String> = extractor.recognizers().iter().map(|r| r.name()).collect();
+        let key = scan_key(&groups);
+        assert_eq!(
+            checkpoint_unusable_reason(&repo, &checkpoint, &key, &fork, head, None, &pr_formats),
+            Some("different repository".to_string())
+        );
+
+        // The fork's issue is only picked up by rescanning
+        let rescanned =
+            scan_deleted_crash_tests(dir.path(), Some(checkpoint), None, &scan_options(&groups, &extractor, &fork, 1))
+                .unwrap();
+        let issues: Vec<u64> = rescanned.deleted_files[0].issues.iter().map(|i| i.number).collect();
+        assert_eq!(issues, vec![1, 5]);
+    }
+
+    #[test]
+    fn test_parallel_scan_matches_serial() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let extractor = PrExtractor::default();
+        let github_repo = GitHubRepo::default();
+        let groups = Config::default().watch;
+        let options = scan_options(&groups, &extractor, &github_repo, 1);
+
+        let initial: Vec<(String, String)> = (1..=20)
+            .map(|n| (format!("tests/crashes/{}.rs", n), format!("// test {}\nfn main() {{}}\n", n)))
+            .collect();
+        let changes: Vec<(&str, Option<&str>)> = initial
+            .iter()
+            .map(|(path, content)| (path.as_str(), Some(content.as_str())))
+            .collect();
+        commit(&repo, "Initial", &changes);
+
+        for n in 1..=20 {
+            let path = format!("tests/crashes/{}.rs", n);
+            commit(&repo, "Unrelated change", &[("README.md", Some(path.as_str()))]);
+            if n % 3 == 0 {
+                // Promote to a ui test instead of deleting
+                let content = format!("// test {}\nfn main() {{}}\n", n);
+                let new_path = format!("tests/ui/issue-{}.rs", n);
+                commit(&repo, &format!("Auto merge of #{} - a:b, r=c", 100 + n), &[
+                    (path.as_str(), None),
+                    (new_path.as_str(), Some(content.as_str())),
+                ]);
+            } else {
+                commit(&repo, &format!("Fix ICE (#{})", 100 + n), &[(path.as_str(), None)]);
+            }
+        }
+
+        let serial = scan_deleted_crash_tests(dir.path(), None, None, &options).unwrap();
+        for jobs in [2, 3, 8] {
+            let parallel_options = scan_options(&groups, &extractor, &github_repo, jobs);
+            let parallel =
+                scan_deleted_crash_tests(dir.path(), None, None, &parallel_options).unwrap();
+            assert_eq!(format!("{:?}", parallel.deleted_files), format!("{:?}", serial.deleted_files));
+        }
+        assert_eq!(serial.deleted_files.len(), 20);
+        assert_eq!(serial.deleted_files.iter().filter(|f| f.is_moved()).count(), 6);
+    }
+
+    #[test]
+    fn test_rollup_messages() {
+        let rollup = "Auto merge of #147900 - Zalathar:rollup-ril6jsi, r=Zalathar\n\n\
+                      Rollup of 5 pull requests\n\nSuccessful merges:\n\n - #147826 (Fix ICE)\n";
+        assert!(is_rollup_message(rollup));
+        assert!(!is_rollup_message("Auto merge of #12345 - username:branch, r=reviewer"));
+    }
 }
@@ -1,65 +1,593 @@
+use crate::cache::{self, CachedIssues};
+use crate::issues::{self, IssueMetadata, IssueStateProvider, IssueStates};
+use crate::progress::Progress;
 use anyhow::{Context, Result};
-use octocrab::Octocrab;
-use std::collections::HashSet;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use octocrab::models::{Event, IssueState};
+use octocrab::{Octocrab, Page};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
 
-/// Fetch all open issues from rust-lang/rust repository
-/// Returns a HashSet of issue numbers for O(1) lookup
-pub async fn fetch_all_open_issues(
+/// Issues per page of the REST listing
+const LIST_PAGE_SIZE: u64 = 100;
+/// Issues per GraphQL query, each looked up through an aliased `issueOrPullRequest(number:)` field
+const GRAPHQL_BATCH_SIZE: usize = 100;
+/// Issue fields fetched by batched queries, matching what the cache keeps
+const GRAPHQL_ISSUE_FIELDS: &str = "title state stateReason closedAt updatedAt labels(first: 20) { nodes { name } }";
+/// Issues whose close details are fetched at the same time
+const CLOSE_DETAILS_CONCURRENCY: usize = 8;
+/// REST API rate limit for unauthenticated clients
+const UNAUTHENTICATED_REQUESTS_PER_HOUR: usize = 60;
+
+/// A GitHub repository, e.g. rust-lang/rust
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitHubRepo {
+    pub owner: String,
+    pub name: String,
+}
+
+impl GitHubRepo {
+    pub fn new(owner: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            owner: owner.into(),
+            name: name.into(),
+        }
+    }
+
+    /// Parse a GitHub remote URL
+    /// Examples:
+    /// - "https://github.com/rust-lang/rust.git" -> rust-lang/rust
+    /// - "git@github.com:rust-lang/rust-clippy.git" -> rust-lang/rust-clippy
+    /// - "ssh://git@github.com/rust-lang/rustfmt" -> rust-lang/rustfmt
+    /// - "https://gitlab.com/foo/bar.git" -> None
+    pub fn from_remote_url(url: &str) -> Option<Self> {
+        let url = url.trim();
+        let path = url
+            .strip_prefix("https://github.com/")
+            .or_else(|| url.strip_prefix("http://github.com/"))
+            .or_else(|| url.strip_prefix("git@github.com:"))
+            .or_else(|| url.strip_prefix("ssh://git@github.com/"))
+            .or_else(|| url.strip_prefix("git://github.com/"))?;
+
+        let path = path.trim_end_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        let (owner, name) = path.split_once('/')?;
+
+        if owner.is_empty() || name.is_empty() || name.contains('/') {
+            return None;
+        }
+
+        Some(Self::new(owner, name))
+    }
+
+    pub fn issue_url(&self, number: u64) -> String {
+        format!("https://github.com/{}/{}/issues/{}", self.owner, self.name, number)
+    }
+
+    pub fn pull_url(&self, number: u64) -> String {
+        format!("https://github.com/{}/{}/pull/{}", self.owner, self.name, number)
+    }
+}
+
+impl Default for GitHubRepo {
+    fn default() -> Self {
+        Self::new("rust-lang", "rust")
+    }
+}
+
+impl fmt::Display for GitHubRepo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.name)
+    }
+}
+
+/// Details about a closed issue that still has crash tests
+#[derive(Debug, Clone)]
+pub struct ClosedIssue {
+    pub number: u64,
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Commit referenced by the "closed" timeline event, if any
+    pub closing_commit: Option<String>,
+    /// Last pull request that cross-referenced the issue before it was closed
+    pub referencing_pr: Option<u64>,
+}
+
+/// How the GitHub provider looks up issue states
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum IssueLookup {
+    /// Batched queries when they take fewer requests than listing every open
+    /// issue and there is no cache to keep up to date
+    #[default]
+    Auto,
+    /// GraphQL queries for exactly the referenced issues (needs a token)
+    Batched,
+    /// List issues and save them to the cache: only the changes when
+    /// refreshing a recent cache, otherwise every open issue
+    List,
+}
+
+/// Live issue states from the GitHub API
+/// Listings are saved to the cache for later runs, and an existing cache is
+/// refreshed with just the changes since it was written. Batched lookups only
+/// cover the referenced issues and are not cached, so they are only picked
+/// automatically when there is no cache.
+#[derive(Debug, Clone)]
+pub struct GitHubProvider {
+    github_repo: GitHubRepo,
     github_token: Option<String>,
-    verbose: bool,
-) -> Result<HashSet<u64>> {
-    // Build octocrab client with optional authentication
+    lookup: IssueLookup,
+    full_refresh: bool,
+    cache_dir: PathBuf,
+    base_uri: Option<String>,
+    progress: Progress,
+}
+
+impl GitHubProvider {
+    pub fn new(github_repo: GitHubRepo, github_token: Option<String>) -> Self {
+        Self {
+            github_repo,
+            github_token,
+            lookup: IssueLookup::default(),
+            full_refresh: false,
+            cache_dir: cache::default_cache_dir(),
+            base_uri: None,
+            progress: Progress::default(),
+        }
+    }
+
+    /// Where progress messages and warnings go (default: nowhere)
+    pub fn progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn lookup(mut self, lookup: IssueLookup) -> Self {
+        self.lookup = lookup;
+        self
+    }
+
+    /// API endpoint to use instead of https://api.github.com
+    pub fn base_uri(mut self, base_uri: impl Into<String>) -> Self {
+        self.base_uri = Some(base_uri.into());
+        self
+    }
+
+    /// List every open issue instead of only the changes since the cache was written
+    pub fn full_refresh(mut self, full_refresh: bool) -> Self {
+        self.full_refresh = full_refresh;
+        self
+    }
+
+    /// Directory to read and write the issue cache in
+    pub fn cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = cache_dir.into();
+        self
+    }
+
+    /// The cache to refresh, if there is a readable one
+    fn existing_cache(&self) -> Option<CachedIssues> {
+        if self.lookup == IssueLookup::Batched || !cache::cache_exists(&self.cache_dir, &self.github_repo) {
+            return None;
+        }
+
+        match cache::load_cache(&self.cache_dir, &self.github_repo, &self.progress) {
+            Ok(cached) => Some(cached),
+            Err(e) => {
+                self.progress.message(format!("Warning: ignoring unreadable cache: {:#}\n", e));
+                None
+            }
+        }
+    }
+
+    /// Whether `cached` is recent enough to only fetch the changes since it was written
+    fn refreshes_incrementally(&self, cached: &CachedIssues) -> bool {
+        if self.full_refresh {
+            return false;
+        }
+        if cached.needs_full_sync(Utc::now()) {
+            self.progress.detail("The cache is due for a full resync\n");
+            return false;
+        }
+        true
+    }
+
+    /// Apply the issues and pull requests updated since the cache was written
+    async fn refresh_incrementally(&self, octocrab: &Octocrab, mut cached: CachedIssues) -> Result<CachedIssues> {
+        let fetched_at = Utc::now();
+        let updates = fetch_updated_issues(octocrab, &self.github_repo, cached.timestamp, &self.progress)
+            .await
+            .context("Failed to fetch updated issues from GitHub")?;
+
+        cached.apply_updates(&updates, fetched_at);
+
+        if !self.progress.is_verbose() {
+            self.progress.message(format!(
+                "Updated cache with {} changed issues and {} changed pull requests ({} open issues)\n",
+                updates.issues.len(),
+                updates.pull_requests.len(),
+                cached.issue_count
+            ));
+        }
+
+        Ok(cached)
+    }
+
+    /// Whether to use batched queries for `needed` issues
+    async fn use_batched(&self, octocrab: &Octocrab, needed: usize) -> Result<bool> {
+        match self.lookup {
+            IssueLookup::List => Ok(false),
+            IssueLookup::Batched if self.github_token.is_none() => {
+                anyhow::bail!("Batched issue lookup uses the GraphQL API, which needs a GitHub token")
+            }
+            IssueLookup::Batched => Ok(true),
+            // GraphQL is not available without a token
+            IssueLookup::Auto if self.github_token.is_none() => Ok(false),
+            // Batched results can't keep an existing cache up to date
+            IssueLookup::Auto if cache::cache_exists(&self.cache_dir, &self.github_repo) => Ok(false),
+            IssueLookup::Auto => {
+                let open_issues = fetch_open_issue_count(octocrab, &self.github_repo)
+                    .await
+                    .context("Failed to fetch repository details from GitHub")?;
+                Ok(batched_is_cheaper(needed, open_issues))
+            }
+        }
+    }
+
+    /// Add the referenced issues the cache has no entry for, typically ones
+    /// closed before it was first written, so their close reason and title are
+    /// cached too
+    /// Needs a token for the batched queries; failures only cost the metadata,
+    /// the issues count as closed either way.
+    async fn look_up_unknown(&self, octocrab: &Octocrab, cached: &mut CachedIssues, numbers: &[u64]) {
+        let pull_requests = cached.pull_requests();
+        let unknown: Vec<u64> = numbers
+            .iter()
+            .copied()
+            .filter(|&number| cached.issue(number).is_none() && !pull_requests.contains(&number))
+            .collect();
+        if unknown.is_empty() || self.github_token.is_none() {
+            return;
+        }
+
+        match fetch_issues(octocrab, &self.github_repo, &unknown, &self.progress).await {
+            Ok(listing) => {
+                let timestamp = cached.timestamp;
+                cached.apply_updates(&listing, timestamp);
+                if !self.progress.is_verbose() {
+                    self.progress.message(format!("Looked up {} issues missing from the cache\n", unknown.len()));
+                }
+            }
+            Err(e) => {
+                let message = format!("{:#}", e);
+                let message = message.lines().next().unwrap_or_default();
+                self.progress.message(format!(
+                    "Warning: failed to look up {} issues missing from the cache: {}\n",
+                    unknown.len(),
+                    message
+                ));
+            }
+        }
+    }
+
+    /// Fetch close details for `candidates`, issues that are not in the open set
+    /// Issues `issue_states` has closed metadata for only need their timeline.
+    /// The others are looked up first and skipped if they turn out to be open
+    /// or don't exist. A few issues are fetched at a time.
+    pub async fn closed_issue_details(&self, candidates: &[u64], issue_states: &IssueStates) -> Result<Vec<ClosedIssue>> {
+        let known_closed = |number: &u64| {
+            issue_states
+                .issues
+                .get(number)
+                .filter(|issue| issue.state == issues::IssueState::Closed)
+        };
+
+        // Every issue takes at least one timeline request, unconfirmed ones another
+        let requests = candidates.len() + candidates.iter().filter(|&n| known_closed(n).is_none()).count();
+        if self.github_token.is_none() && requests > UNAUTHENTICATED_REQUESTS_PER_HOUR {
+            anyhow::bail!(
+                "Fetching close details for {} issues takes at least {} GitHub API requests, more than the {} an hour allowed without a GitHub token",
+                candidates.len(),
+                requests,
+                UNAUTHENTICATED_REQUESTS_PER_HOUR
+            );
+        }
+
+        let octocrab = build_client(self.github_token.clone(), self.base_uri.as_deref())?;
+
+        self.progress.detail(format!(
+            "Fetching close details for {} issues from {}...",
+            candidates.len(),
+            self.github_repo
+        ));
+
+        let closed_issues: Vec<Option<ClosedIssue>> = stream::iter(candidates)
+            .map(|&number| self.closed_issue(&octocrab, number, known_closed(&number)))
+            .buffered(CLOSE_DETAILS_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        self.progress.detail("");
+
+        Ok(closed_issues.into_iter().flatten().collect())
+    }
+
+    /// Close details for one issue, None if it is open or doesn't exist
+    async fn closed_issue(
+        &self,
+        octocrab: &Octocrab,
+        number: u64,
+        known_closed: Option<&IssueMetadata>,
+    ) -> Result<Option<ClosedIssue>> {
+        let github_repo = &self.github_repo;
+        let closed_at = match known_closed {
+            Some(issue) => issue.closed_at,
+            None => {
+                let issue = match octocrab.issues(&github_repo.owner, &github_repo.name).get(number).await {
+                    Ok(issue) => issue,
+                    Err(octocrab::Error::GitHub { source, .. }) if source.status_code.as_u16() == 404 => {
+                        self.progress.detail(format!("  Issue #{} not found, skipping", number));
+                        return Ok(None);
+                    }
+                    Err(e) => {
+                        return Err(e).context(format!("Failed to fetch issue #{}", number));
+                    }
+                };
+
+                // The issue states may be stale - don't report issues that are actually open
+                if issue.state == IssueState::Open {
+                    self.progress.detail(format!("  Issue #{} is open, skipping", number));
+                    return Ok(None);
+                }
+                issue.closed_at
+            }
+        };
+
+        let (closing_commit, referencing_pr) = fetch_close_references(octocrab, github_repo, number)
+            .await
+            .context(format!("Failed to fetch timeline for issue #{}", number))?;
+
+        self.progress.detail(format!("  Fetched issue #{}", number));
+
+        Ok(Some(ClosedIssue {
+            number,
+            closed_at,
+            closing_commit,
+            referencing_pr,
+        }))
+    }
+}
+
+#[async_trait]
+impl IssueStateProvider for GitHubProvider {
+    async fn issue_states(&self, numbers: &[u64]) -> Result<IssueStates> {
+        let octocrab = build_client(self.github_token.clone(), self.base_uri.as_deref())?;
+
+        // An existing cache only needs the changes since it was written
+        let existing = match self.existing_cache() {
+            Some(cached) if self.refreshes_incrementally(&cached) => {
+                let mut cached = self.refresh_incrementally(&octocrab, cached).await?;
+                self.look_up_unknown(&octocrab, &mut cached, numbers).await;
+                cache::save_cache(&self.cache_dir, &self.github_repo, &cached)
+                    .context("Failed to save cache")?;
+                return Ok(cached.issue_states(numbers, false));
+            }
+            existing => existing,
+        };
+
+        if self.use_batched(&octocrab, numbers.len()).await? {
+            let fetched_at = Utc::now();
+            let listing = fetch_issues(&octocrab, &self.github_repo, numbers, &self.progress)
+                .await
+                .context("Failed to look up issues on GitHub")?;
+
+            if !self.progress.is_verbose() {
+                self.progress.message(format!("Looked up {} issues in batched queries\n", numbers.len()));
+            }
+
+            return Ok(IssueStates {
+                states: listing.issues.iter().map(|(&number, issue)| (number, issue.state)).collect(),
+                pull_requests: listing.pull_requests,
+                issues: listing.issues,
+                fetched_at,
+                cached: false,
+            });
+        }
+
+        let fetched_at = Utc::now();
+        let open = fetch_all_open_issues(&octocrab, &self.github_repo, &self.progress)
+            .await
+            .context("Failed to fetch open issues from GitHub")?;
+
+        let mut cached = CachedIssues::new(&self.github_repo, open.issues.into_values(), open.pull_requests, fetched_at);
+        if let Some(previous) = &existing {
+            cached.keep_known(previous);
+        }
+        self.look_up_unknown(&octocrab, &mut cached, numbers).await;
+        cache::save_cache(&self.cache_dir, &self.github_repo, &cached)
+            .context("Failed to save cache")?;
+
+        if !self.progress.is_verbose() {
+            self.progress.message(format!("Cached {} open issues\n", cached.issue_count));
+        }
+
+        Ok(cached.issue_states(numbers, false))
+    }
+}
+
+/// Whether looking up `needed` issues in batches takes fewer requests than
+/// listing `open_issues` open issues page by page
+fn batched_is_cheaper(needed: usize, open_issues: u64) -> bool {
+    let batches = needed.div_ceil(GRAPHQL_BATCH_SIZE) as u64;
+    let pages = open_issues.div_ceil(LIST_PAGE_SIZE).max(1);
+    batches < pages
+}
+
+/// Build octocrab client with optional authentication
+fn build_client(github_token: Option<String>, base_uri: Option<&str>) -> Result<Octocrab> {
+    let mut builder = Octocrab::builder();
+    if let Some(base_uri) = base_uri {
+        builder = builder
+            .base_uri(base_uri)
+            .context("Invalid GitHub API URL")?;
+    }
+
     let octocrab = if let Some(token) = github_token {
-        Octocrab::builder()
+        builder
             .personal_token(token)
             .build()
             .context("Failed to build authenticated GitHub client")?
     } else {
-        if verbose {
-            println!("Note: Using unauthenticated API (60 requests/hour limit)");
-            println!("Set GITHUB_TOKEN environment variable for higher limits (5,000 requests/hour)");
-            println!();
-        }
-        Octocrab::builder()
+        builder
             .build()
             .context("Failed to build GitHub client")?
     };
 
-    let mut open_issue_numbers = HashSet::new();
-    let mut page_count = 0u32;
+    Ok(octocrab)
+}
+
+/// The fields of a listed issue the audit uses
+#[derive(Debug, Deserialize)]
+struct ListedIssue {
+    number: u64,
+    title: String,
+    state: issues::IssueState,
+    state_reason: Option<issues::StateReason>,
+    #[serde(default)]
+    labels: Vec<ListedLabel>,
+    closed_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    /// Present when the item is a pull request
+    pull_request: Option<serde::de::IgnoredAny>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListedLabel {
+    name: String,
+}
+
+impl From<ListedIssue> for IssueMetadata {
+    fn from(issue: ListedIssue) -> Self {
+        Self {
+            number: issue.number,
+            title: Some(issue.title),
+            state: issue.state,
+            state_reason: issue.state_reason,
+            labels: issue.labels.into_iter().map(|label| label.name).collect(),
+            closed_at: issue.closed_at,
+            updated_at: issue.updated_at,
+        }
+    }
+}
+
+/// Issues and pull requests from the issue listing, which returns both together
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IssueListing {
+    pub issues: BTreeMap<u64, IssueMetadata>,
+    /// Pull requests, whatever their state
+    pub pull_requests: BTreeSet<u64>,
+}
+
+impl IssueListing {
+    fn insert(&mut self, item: ListedIssue) {
+        if item.pull_request.is_some() {
+            self.pull_requests.insert(item.number);
+        } else {
+            self.issues.insert(item.number, item.into());
+        }
+    }
 
-    if verbose {
-        println!("Fetching open issues from rust-lang/rust...");
+    /// Numbers of the open issues
+    pub fn open_issues(&self) -> HashSet<u64> {
+        self.issues
+            .values()
+            .filter(|issue| issue.state == issues::IssueState::Open)
+            .map(|issue| issue.number)
+            .collect()
     }
+}
+
+/// Repository details used to estimate the size of the issue listing
+#[derive(Debug, Deserialize)]
+struct RepositoryInfo {
+    /// Open issues and pull requests
+    open_issues_count: u64,
+}
+
+/// Number of open issues (including pull requests) in the repository
+async fn fetch_open_issue_count(octocrab: &Octocrab, github_repo: &GitHubRepo) -> Result<u64> {
+    let route = format!("/repos/{}/{}", github_repo.owner, github_repo.name);
+    let info: RepositoryInfo = octocrab.get(route, None::<&()>).await?;
+    Ok(info.open_issues_count)
+}
+
+/// Fetch all open issues from the repository
+/// Pull requests are listed too and kept apart from the issues
+pub async fn fetch_all_open_issues(
+    octocrab: &Octocrab,
+    github_repo: &GitHubRepo,
+    progress: &Progress,
+) -> Result<IssueListing> {
+    progress.detail(format!("Fetching open issues from {}...", github_repo));
+
+    let params = serde_json::json!({ "state": "open", "per_page": LIST_PAGE_SIZE });
+    fetch_issue_listing(octocrab, github_repo, &params, progress).await
+}
+
+/// Fetch the issues and pull requests updated since `since`, open or closed
+pub async fn fetch_updated_issues(
+    octocrab: &Octocrab,
+    github_repo: &GitHubRepo,
+    since: DateTime<Utc>,
+    progress: &Progress,
+) -> Result<IssueListing> {
+    progress.detail(format!("Fetching issues in {} updated since {}...", github_repo, since.to_rfc3339()));
+
+    let params = serde_json::json!({
+        "state": "all",
+        "since": since.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        "per_page": LIST_PAGE_SIZE,
+    });
+    fetch_issue_listing(octocrab, github_repo, &params, progress).await
+}
+
+/// Page through the issue listing with the given query parameters
+async fn fetch_issue_listing(
+    octocrab: &Octocrab,
+    github_repo: &GitHubRepo,
+    params: &serde_json::Value,
+    progress: &Progress,
+) -> Result<IssueListing> {
+    let mut listing = IssueListing::default();
+    let mut page_count = 0u32;
 
     // Use paginate_stream for cursor-based pagination
-    let mut issues_stream = octocrab
-        .issues("rust-lang", "rust")
-        .list()
-        .state(octocrab::params::State::Open)
-        .per_page(100)
-        .send()
+    let route = format!("/repos/{}/{}/issues", github_repo.owner, github_repo.name);
+    let mut issues_stream: Page<ListedIssue> = octocrab
+        .get(route, Some(params))
         .await
-        .context("Failed to start fetching open issues")?;
+        .context("Failed to start fetching issues")?;
 
     loop {
         let page_items = issues_stream.items.len();
 
-        // Add issue numbers to our set
-        for issue in &issues_stream.items {
-            open_issue_numbers.insert(issue.number);
+        // Sort the page into issues and pull requests
+        for issue in issues_stream.items.drain(..) {
+            listing.insert(issue);
         }
 
         page_count += 1;
 
-        if verbose {
-            println!(
-                "  Fetched page {} ({} issues, {} total so far)",
-                page_count,
-                page_items,
-                open_issue_numbers.len()
-            );
-        }
+        progress.detail(format!(
+            "  Fetched page {} ({} issues, {} total so far)",
+            page_count,
+            page_items,
+            listing.issues.len()
+        ));
 
         // Get next page using cursor-based pagination
         match octocrab.get_page(&issues_stream.next).await {
@@ -68,18 +596,609 @@ pub async fn fetch_all_open_issues(
             }
             Ok(None) => break, // No more pages
             Err(e) => {
-                return Err(e).context(format!("Failed to fetch open issues (page {})", page_count + 1));
+                return Err(e).context(format!("Failed to fetch issues (page {})", page_count + 1));
+            }
+        }
+    }
+
+    progress.detail(format!(
+        "\nFetched {} issues ({} pull requests skipped) in {} pages\n",
+        listing.issues.len(),
+        listing.pull_requests.len(),
+        page_count
+    ));
+
+    Ok(listing)
+}
+
+/// GraphQL response for a batch of aliased `issueOrPullRequest(number:)` lookups
+#[derive(Debug, Deserialize)]
+struct IssueStatesResponse {
+    data: Option<IssueStatesData>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueStatesData {
+    /// Aliased lookups (`i12345`), null for numbers that don't exist
+    repository: Option<HashMap<String, Option<GraphQlItem>>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "__typename")]
+enum GraphQlItem {
+    Issue(GraphQlIssue),
+    PullRequest {},
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlIssue {
+    title: String,
+    state: issues::IssueState,
+    state_reason: Option<issues::StateReason>,
+    closed_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    labels: Option<GraphQlLabels>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlLabels {
+    nodes: Vec<ListedLabel>,
+}
+
+impl GraphQlIssue {
+    fn into_metadata(self, number: u64) -> IssueMetadata {
+        IssueMetadata {
+            number,
+            title: Some(self.title),
+            state: self.state,
+            state_reason: self.state_reason,
+            labels: self
+                .labels
+                .map(|labels| labels.nodes.into_iter().map(|label| label.name).collect())
+                .unwrap_or_default(),
+            closed_at: self.closed_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    message: String,
+}
+
+/// Query looking up each issue through an aliased field
+fn issue_states_query(numbers: &[u64]) -> String {
+    let fields: Vec<String> = numbers
+        .iter()
+        .map(|number| {
+            format!(
+                "i{0}: issueOrPullRequest(number: {0}) {{ __typename ... on Issue {{ {1} }} }}",
+                number, GRAPHQL_ISSUE_FIELDS
+            )
+        })
+        .collect();
+    format!(
+        "query($owner: String!, $name: String!) {{ repository(owner: $owner, name: $name) {{ {} }} }}",
+        fields.join(" ")
+    )
+}
+
+/// Issues and pull request numbers from a batch response
+/// Numbers that don't exist are left out.
+fn parse_issue_states(response: IssueStatesResponse) -> Result<IssueListing> {
+    // Unresolvable numbers come back as NOT_FOUND errors next to the data
+    if let Some(error) = response
+        .errors
+        .iter()
+        .find(|error| error.kind.as_deref() != Some("NOT_FOUND"))
+    {
+        anyhow::bail!("GitHub GraphQL error: {}", error.message);
+    }
+
+    let Some(repository) = response.data.and_then(|data| data.repository) else {
+        let message = response.errors.first().map(|e| e.message.as_str()).unwrap_or("no data");
+        anyhow::bail!("GitHub GraphQL error: {}", message);
+    };
+
+    let mut listing = IssueListing::default();
+    for (alias, item) in repository {
+        let number = alias
+            .strip_prefix('i')
+            .and_then(|n| n.parse().ok())
+            .with_context(|| format!("Unexpected field {:?} in GraphQL response", alias))?;
+        match item {
+            Some(GraphQlItem::Issue(issue)) => {
+                listing.issues.insert(number, issue.into_metadata(number));
+            }
+            Some(GraphQlItem::PullRequest {}) => {
+                listing.pull_requests.insert(number);
+            }
+            None => {}
+        }
+    }
+
+    Ok(listing)
+}
+
+/// Look up specific issues, open or closed, about 100 per GraphQL query
+pub async fn fetch_issues(
+    octocrab: &Octocrab,
+    github_repo: &GitHubRepo,
+    numbers: &[u64],
+    progress: &Progress,
+) -> Result<IssueListing> {
+    let mut listing = IssueListing::default();
+
+    progress.detail(format!("Looking up {} issues in {}...", numbers.len(), github_repo));
+
+    for (index, batch) in numbers.chunks(GRAPHQL_BATCH_SIZE).enumerate() {
+        let payload = serde_json::json!({
+            "query": issue_states_query(batch),
+            "variables": { "owner": github_repo.owner, "name": github_repo.name },
+        });
+        let response: IssueStatesResponse = octocrab
+            .graphql(&payload)
+            .await
+            .with_context(|| format!("Failed to look up issues (batch {})", index + 1))?;
+        let batch_listing = parse_issue_states(response)?;
+        listing.issues.extend(batch_listing.issues);
+        listing.pull_requests.extend(batch_listing.pull_requests);
+
+        progress.detail(format!("  Looked up batch {} ({} issues)", index + 1, batch.len()));
+    }
+
+    progress.detail("");
+
+    Ok(listing)
+}
+
+/// Walk an issue's timeline to find what closed it
+/// Returns the commit from the last "closed" event and the last PR that
+/// cross-referenced the issue before that
+async fn fetch_close_references(
+    octocrab: &Octocrab,
+    github_repo: &GitHubRepo,
+    number: u64,
+) -> Result<(Option<String>, Option<u64>)> {
+    let mut page = octocrab
+        .issues(&github_repo.owner, &github_repo.name)
+        .list_timeline_events(number)
+        .per_page(100)
+        .send()
+        .await?;
+
+    let mut closing_commit = None;
+    let mut referencing_pr = None;
+    let mut last_pr_reference = None;
+
+    loop {
+        for event in &page.items {
+            match event.event {
+                Event::CrossReferenced => {
+                    if let Some(source) = &event.source {
+                        if source.issue.pull_request.is_some() {
+                            last_pr_reference = Some(source.issue.number);
+                        }
+                    }
+                }
+                Event::Closed => {
+                    closing_commit = event.commit_id.clone();
+                    referencing_pr = last_pr_reference;
+                }
+                _ => {}
             }
         }
+
+        match octocrab.get_page(&page.next).await? {
+            Some(next_page) => page = next_page,
+            None => break,
+        }
+    }
+
+    Ok((closing_commit, referencing_pr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_remote_url() {
+        let cases = [
+            ("https://github.com/rust-lang/rust.git", Some(("rust-lang", "rust"))),
+            ("https://github.com/rust-lang/rust", Some(("rust-lang", "rust"))),
+            ("https://github.com/rust-lang/rust/", Some(("rust-lang", "rust"))),
+            ("git@github.com:rust-lang/rust-clippy.git", Some(("rust-lang", "rust-clippy"))),
+            ("ssh://git@github.com/rust-lang/rustfmt", Some(("rust-lang", "rustfmt"))),
+            ("https://gitlab.com/foo/bar.git", None),
+            ("https://github.com/rust-lang", None),
+            ("https://github.com/rust-lang/rust/tree/master", None),
+        ];
+
+        for (url, expected) in cases {
+            assert_eq!(
+                GitHubRepo::from_remote_url(url),
+                expected.map(|(owner, name)| GitHubRepo::new(owner, name)),
+                "url: {}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_urls() {
+        let repo = GitHubRepo::new("rust-lang", "rust-clippy");
+        assert_eq!(repo.to_string(), "rust-lang/rust-clippy");
+        assert_eq!(repo.issue_url(12), "https://github.com/rust-lang/rust-clippy/issues/12");
+        assert_eq!(repo.pull_url(34), "https://github.com/rust-lang/rust-clippy/pull/34");
+    }
+
+    #[test]
+    fn test_batched_is_cheaper() {
+        // rust-lang/rust: 10k open issues take 100 pages
+        assert!(batched_is_cheaper(200, 10_000));
+        assert!(batched_is_cheaper(9_900, 10_000));
+        assert!(!batched_is_cheaper(10_000, 10_000));
+        // Small repositories list in a single page
+        assert!(!batched_is_cheaper(1, 50));
+        // Nothing to look up takes no requests at all
+        assert!(batched_is_cheaper(0, 50));
     }
 
-    if verbose {
-        println!(
-            "\nFetched {} open issues in {} pages\n",
-            open_issue_numbers.len(),
-            page_count
-        );
+    #[test]
+    fn test_parse_issue_states() {
+        let response: IssueStatesResponse = serde_json::from_value(serde_json::json!({
+            "data": { "repository": {
+                "i1": { "__typename": "Issue", "title": "ICE", "state": "OPEN", "stateReason": null,
+                         "closedAt": null, "updatedAt": "2024-06-01T12:00:00Z", "labels": { "nodes": [{ "name": "I-ICE" }] } },
+                "i2": { "__typename": "Issue", "title": "Dup", "state": "CLOSED", "stateReason": "DUPLICATE",
+                         "closedAt": "2024-05-31T08:00:00Z", "updatedAt": "2024-05-31T08:00:00Z", "labels": null },
+                "i3": null,
+                "i4": { "__typename": "PullRequest" },
+            } },
+            "errors": [{ "type": "NOT_FOUND", "message": "Could not resolve to an issue or pull request with the number of 3." }],
+        }))
+        .unwrap();
+        let listing = parse_issue_states(response).unwrap();
+        assert_eq!(listing.open_issues(), [1].into_iter().collect());
+        assert_eq!(listing.issues.keys().copied().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(listing.issues[&1].labels, vec!["I-ICE"]);
+        let closed = &listing.issues[&2];
+        assert_eq!(closed.title.as_deref(), Some("Dup"));
+        assert_eq!(closed.state_reason, Some(issues::StateReason::Duplicate));
+        assert_eq!(closed.closed_at, Some("2024-05-31T08:00:00Z".parse().unwrap()));
+        assert_eq!(listing.pull_requests.into_iter().collect::<Vec<_>>(), vec![4]);
+
+        let response: IssueStatesResponse = serde_json::from_value(serde_json::json!({
+            "data": null,
+            "errors": [{ "type": "RATE_LIMITED", "message": "API rate limit exceeded" }],
+        }))
+        .unwrap();
+        let error = parse_issue_states(response).unwrap_err();
+        assert!(error.to_string().contains("API rate limit exceeded"));
     }
 
-    Ok(open_issue_numbers)
+    mod mock_server {
+        use super::*;
+        use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+        use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+        fn provider(server: &MockServer, cache_dir: &tempfile::TempDir) -> GitHubProvider {
+            GitHubProvider::new(GitHubRepo::new("example", "project"), Some("token".to_string()))
+                .base_uri(server.uri())
+                .cache_dir(cache_dir.path())
+        }
+
+        async fn mock_open_issue_count(server: &MockServer, count: u64) {
+            Mock::given(method("GET"))
+                .and(path("/repos/example/project"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "full_name": "example/project",
+                    "open_issues_count": count,
+                })))
+                .mount(server)
+                .await;
+        }
+
+        /// An open item of the REST issue listing
+        fn listed_issue(number: u64, pull_request: bool) -> serde_json::Value {
+            let mut issue = serde_json::json!({
+                "number": number,
+                "title": format!("ICE #{}", number),
+                "state": "open",
+                "state_reason": null,
+                "labels": [{ "id": 1, "name": "I-ICE" }, { "id": 2, "name": "T-compiler" }],
+                "closed_at": null,
+                "updated_at": "2024-06-01T12:00:00Z",
+            });
+            if pull_request {
+                issue["pull_request"] = serde_json::json!({ "url": format!("https://api.github.com/pulls/{}", number) });
+            }
+            issue
+        }
+
+        /// Answers batched lookups: even numbers are open, 7 is a pull request and 9 doesn't exist
+        fn graphql_responder(request: &Request) -> ResponseTemplate {
+            let body: serde_json::Value = request.body_json().unwrap();
+            assert_eq!(body["variables"]["owner"], "example");
+
+            let query = body["query"].as_str().unwrap();
+            let mut repository = serde_json::Map::new();
+            let mut errors = Vec::new();
+            for field in query.split("issueOrPullRequest(number: ").skip(1) {
+                let number: u64 = field.split(')').next().unwrap().parse().unwrap();
+                let issue = if number == 7 {
+                    serde_json::json!({ "__typename": "PullRequest" })
+                } else if number == 9 {
+                    errors.push(serde_json::json!({ "type": "NOT_FOUND", "message": "Not found" }));
+                    serde_json::Value::Null
+                } else {
+                    let (state, reason) = if number.is_multiple_of(2) { ("OPEN", None) } else { ("CLOSED", Some("COMPLETED")) };
+                    serde_json::json!({
+                        "__typename": "Issue",
+                        "title": format!("ICE #{}", number),
+                        "state": state,
+                        "stateReason": reason,
+                        "closedAt": reason.map(|_| "2024-05-01T00:00:00Z"),
+                        "updatedAt": "2024-05-01T00:00:00Z",
+                        "labels": { "nodes": [{ "name": "I-ICE" }] },
+                    })
+                };
+                repository.insert(format!("i{}", number), issue);
+            }
+
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": { "repository": repository },
+                "errors": errors,
+            }))
+        }
+
+        #[tokio::test]
+        async fn test_batched_lookup() {
+            let server = MockServer::start().await;
+            mock_open_issue_count(&server, 12_000).await;
+            Mock::given(method("POST"))
+                .and(path("/graphql"))
+                .respond_with(graphql_responder)
+                .expect(2)
+                .mount(&server)
+                .await;
+
+            let cache_dir = tempfile::TempDir::new().unwrap();
+            let numbers: Vec<u64> = (1..=150).collect();
+            let issue_states = provider(&server, &cache_dir).issue_states(&numbers).await.unwrap();
+
+            assert!(!issue_states.cached);
+            assert_eq!(issue_states.states.len(), 148);
+            assert!(!issue_states.states.contains_key(&7) && !issue_states.states.contains_key(&9));
+            assert_eq!(issue_states.pull_requests.iter().copied().collect::<Vec<_>>(), vec![7]);
+            assert_eq!(issue_states.states[&150], issues::IssueState::Open);
+            assert_eq!(issue_states.states[&149], issues::IssueState::Closed);
+            assert_eq!(issue_states.open().len(), 75);
+            assert_eq!(issue_states.issues[&149].state_reason, Some(issues::StateReason::Completed));
+            assert_eq!(issue_states.issues[&150].title.as_deref(), Some("ICE #150"));
+        }
+
+        /// Serves a recorded issue listing for requests matching `state` (and `since`)
+        async fn mock_listing(server: &MockServer, state: &str, since: Option<&str>, fixture: &str) {
+            let mut mock = Mock::given(method("GET"))
+                .and(path("/repos/example/project/issues"))
+                .and(query_param("state", state));
+            mock = match since {
+                Some(since) => mock.and(query_param("since", since)),
+                None => mock.and(query_param_is_missing("since")),
+            };
+            mock.respond_with(ResponseTemplate::new(200).set_body_raw(fixture, "application/json"))
+                .expect(1)
+                .mount(server)
+                .await;
+        }
+
+        #[tokio::test]
+        async fn test_incremental_refresh() {
+            let server = MockServer::start().await;
+            mock_listing(&server, "open", None, include_str!("../tests/fixtures/issues_open.json")).await;
+            mock_listing(
+                &server,
+                "all",
+                Some("2024-06-01T00:00:00Z"),
+                include_str!("../tests/fixtures/issues_updated_since.json"),
+            )
+            .await;
+
+            let cache_dir = tempfile::TempDir::new().unwrap();
+            let provider = provider(&server, &cache_dir);
+            let octocrab = build_client(provider.github_token.clone(), provider.base_uri.as_deref()).unwrap();
+
+            let synced_at: DateTime<Utc> = "2024-06-01T00:00:00Z".parse().unwrap();
+            let open = fetch_all_open_issues(&octocrab, &provider.github_repo, &Progress::default()).await.unwrap();
+            let cached = CachedIssues::new(&provider.github_repo, open.issues.into_values(), open.pull_requests, synced_at);
+            assert_eq!(cached.to_hashset(), [100, 101, 103].into_iter().collect());
+            assert_eq!(cached.pull_requests(), [102].into_iter().collect());
+
+            let refreshed = provider.refresh_incrementally(&octocrab, cached).await.unwrap();
+
+            // 101 was closed, 104 opened, PR 102 merged and PR 105 opened;
+            // the merged PR is still known as one
+            assert_eq!(refreshed.to_hashset(), [100, 103, 104].into_iter().collect());
+            assert_eq!(refreshed.issue_count, 3);
+            assert_eq!(refreshed.pull_requests(), [102, 105].into_iter().collect());
+            assert!(refreshed.timestamp > synced_at);
+            assert_eq!(refreshed.last_full_sync, Some(synced_at));
+
+            // Changed issues carry their new metadata, closed ones stay in the cache
+            let issue = |number: u64| refreshed.issues.iter().find(|issue| issue.number == number).unwrap();
+            assert_eq!(issue(100).labels, vec!["I-ICE", "P-high"]);
+            assert_eq!(issue(101).state, issues::IssueState::Closed);
+            assert_eq!(issue(101).state_reason, Some(issues::StateReason::Completed));
+            assert_eq!(issue(101).closed_at, Some("2024-06-01T09:01:00Z".parse().unwrap()));
+            assert_eq!(issue(90).state_reason, Some(issues::StateReason::NotPlanned));
+            assert_eq!(issue(103).updated_at, Some("2024-05-28T09:15:00Z".parse().unwrap()));
+
+            let issue_states = refreshed.issue_states(&[90, 101, 102, 104, 105], false);
+            assert_eq!(issue_states.open(), [100, 103, 104].into_iter().collect());
+            assert_eq!(issue_states.states[&90], issues::IssueState::Closed);
+            assert_eq!(issue_states.issues[&90].state_reason, Some(issues::StateReason::NotPlanned));
+            assert!(!issue_states.states.contains_key(&102) && !issue_states.states.contains_key(&105));
+        }
+
+        #[tokio::test]
+        async fn test_list_lookup() {
+            let server = MockServer::start().await;
+            mock_open_issue_count(&server, 150).await;
+            Mock::given(method("GET"))
+                .and(path("/repos/example/project/issues"))
+                .and(query_param("state", "open"))
+                .and(query_param_is_missing("page"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header(
+                            "link",
+                            format!("<{}/repos/example/project/issues?state=open&page=2>; rel=\"next\"", server.uri()),
+                        )
+                        .set_body_json(serde_json::json!([
+                            listed_issue(10, false),
+                            listed_issue(11, true),
+                        ])),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/repos/example/project/issues"))
+                .and(query_param("page", "2"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([listed_issue(12, false)])))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/graphql"))
+                .respond_with(graphql_responder)
+                .expect(0)
+                .mount(&server)
+                .await;
+
+            // Two batches are more than the two pages of the listing
+            let cache_dir = tempfile::TempDir::new().unwrap();
+            let provider = provider(&server, &cache_dir);
+            let octocrab = build_client(provider.github_token.clone(), provider.base_uri.as_deref()).unwrap();
+            let numbers: Vec<u64> = (1..=150).collect();
+            assert!(!provider.use_batched(&octocrab, numbers.len()).await.unwrap());
+            assert!(provider.clone().lookup(IssueLookup::Batched).use_batched(&octocrab, 1).await.unwrap());
+
+            let open = fetch_all_open_issues(&octocrab, &provider.github_repo, &Progress::default()).await.unwrap();
+            assert_eq!(open.open_issues(), [10, 12].into_iter().collect());
+            let issue = &open.issues[&12];
+            assert_eq!(issue.title.as_deref(), Some("ICE #12"));
+            assert_eq!(issue.labels, vec!["I-ICE", "T-compiler"]);
+            assert_eq!(issue.updated_at, Some("2024-06-01T12:00:00Z".parse().unwrap()));
+            assert_eq!(open.pull_requests, [11].into_iter().collect());
+        }
+
+        #[tokio::test]
+        async fn test_auto_lookup_resyncs_cache() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/repos/example/project/issues"))
+                .and(query_param("state", "open"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                    listed_issue(10, false),
+                    listed_issue(11, true),
+                ])))
+                .expect(1)
+                .mount(&server)
+                .await;
+            // Only for the referenced issue the cache has no entry for
+            Mock::given(method("POST"))
+                .and(path("/graphql"))
+                .respond_with(graphql_responder)
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            // A cache due for its weekly full sync; batched queries for three
+            // issues would be cheaper, but would leave it as it is
+            let cache_dir = tempfile::TempDir::new().unwrap();
+            let github_repo = GitHubRepo::new("example", "project");
+            let synced_at = Utc::now() - chrono::Duration::days(10);
+            let closed = IssueMetadata {
+                state: issues::IssueState::Closed,
+                state_reason: Some(issues::StateReason::NotPlanned),
+                ..IssueMetadata::open(30)
+            };
+            let cached = CachedIssues::new(&github_repo, [IssueMetadata::open(1), closed], [50], synced_at);
+            cache::save_cache(cache_dir.path(), &github_repo, &cached).unwrap();
+
+            let issue_states = provider(&server, &cache_dir).issue_states(&[10, 21, 30]).await.unwrap();
+            assert_eq!(issue_states.open(), [10].into_iter().collect());
+            assert_eq!(issue_states.states[&21], issues::IssueState::Closed);
+            assert_eq!(issue_states.issues[&21].state_reason, Some(issues::StateReason::Completed));
+            assert_eq!(issue_states.issues[&30].state_reason, Some(issues::StateReason::NotPlanned));
+
+            // 1 is no longer listed as open, closed issues and pull requests carry over
+            let saved = cache::load_cache(cache_dir.path(), &github_repo, &Progress::default()).unwrap();
+            assert_eq!(saved.issues.iter().map(|issue| issue.number).collect::<Vec<_>>(), vec![10, 21, 30]);
+            assert_eq!(saved.to_hashset(), [10].into_iter().collect());
+            assert_eq!(saved.issue(21).unwrap().title.as_deref(), Some("ICE #21"));
+            assert_eq!(saved.pull_requests(), [11, 50].into_iter().collect());
+            assert!(saved.timestamp > synced_at);
+            assert_eq!(saved.last_full_sync, Some(saved.timestamp));
+        }
+
+        #[tokio::test]
+        async fn test_closed_issue_details_from_known_states() {
+            let server = MockServer::start().await;
+            // The state and close date are known, so the issue itself is never fetched
+            Mock::given(method("GET"))
+                .and(path("/repos/example/project/issues/30"))
+                .respond_with(ResponseTemplate::new(500))
+                .expect(0)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/repos/example/project/issues/30/timeline"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                    { "event": "closed", "commit_id": "0123456789abcdef" },
+                ])))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let closed_at: DateTime<Utc> = "2024-05-01T00:00:00Z".parse().unwrap();
+            let closed = IssueMetadata {
+                state: issues::IssueState::Closed,
+                closed_at: Some(closed_at),
+                ..IssueMetadata::open(30)
+            };
+            let cache_dir = tempfile::TempDir::new().unwrap();
+            let cached = CachedIssues::new(&GitHubRepo::new("example", "project"), [closed], [], Utc::now());
+            let issue_states = cached.issue_states(&[30], true);
+
+            let details = provider(&server, &cache_dir).closed_issue_details(&[30], &issue_states).await.unwrap();
+            assert_eq!(details.len(), 1);
+            assert_eq!(details[0].closed_at, Some(closed_at));
+            assert_eq!(details[0].closing_commit.as_deref(), Some("0123456789abcdef"));
+        }
+
+        #[tokio::test]
+        async fn test_closed_issue_details_without_token() {
+            let server = MockServer::start().await;
+            let cache_dir = tempfile::TempDir::new().unwrap();
+            let provider = GitHubProvider::new(GitHubRepo::new("example", "project"), None)
+                .base_uri(server.uri())
+                .cache_dir(cache_dir.path());
+
+            let numbers: Vec<u64> = (1..=40).collect();
+            let issue_states = IssueStates::from_open_issues(&HashSet::new(), &HashSet::new(), &numbers, Utc::now(), true);
+            let error = provider.closed_issue_details(&numbers, &issue_states).await.unwrap_err();
+            assert!(error.to_string().contains("at least 80 GitHub API requests"), "{}", error);
+            assert!(server.received_requests().await.unwrap().is_empty());
+        }
+    }
 }
@@ -0,0 +1,172 @@
+use std::path::Path;
+
+/// Line comment syntax of a test header, chosen by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentSyntax {
+    /// `//` comments, for Rust and anything not listed below
+    Slashes,
+    /// `#` comments, for shell and Python scripts and TOML files
+    Hash,
+    /// `<!-- -->` comments, for Markdown and HTML
+    Html,
+}
+
+impl CommentSyntax {
+    pub fn for_path(path: &str) -> Self {
+        let extension = Path::new(path).extension().and_then(|e| e.to_str());
+        match extension {
+            Some("sh" | "bash" | "py" | "toml") => CommentSyntax::Hash,
+            Some("md" | "html") => CommentSyntax::Html,
+            _ => CommentSyntax::Slashes,
+        }
+    }
+
+    /// The text of a comment line, or None if the line isn't a comment
+    fn comment<'a>(&self, line: &'a str) -> Option<&'a str> {
+        match self {
+            CommentSyntax::Slashes => line.strip_prefix("//"),
+            CommentSyntax::Hash => line.strip_prefix('#'),
+            CommentSyntax::Html => line
+                .strip_prefix("<!--")
+                .map(|comment| comment.strip_suffix("-->").unwrap_or(comment)),
+        }
+    }
+}
+
+/// Extract issue numbers from compiletest `known-bug` directives in a test header
+/// Only the leading comment block is scanned, like compiletest does for crash tests.
+/// Cross-repository references are only accepted for `repo` ("owner/name").
+/// Examples:
+/// - "//@ known-bug: #12345" -> [12345]
+/// - "//@ known-bug: rust-lang/rust#12345" -> [12345]
+/// - "//@ known-bug: https://github.com/rust-lang/rust/issues/12345" -> [12345]
+/// - "//@ known-bug: unknown" -> []
+/// - "# known-bug: #12345" in a shell script -> [12345]
+/// - "<!-- known-bug: #12345 -->" in Markdown -> [12345]
+pub fn parse_known_bugs(content: &str, syntax: CommentSyntax, repo: &str) -> Vec<u64> {
+    let mut issues = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // Stop at the first line of actual code
+        let Some(comment) = syntax.comment(line) else {
+            break;
+        };
+
+        // Accept both the current `//@` syntax and the legacy `//` one
+        let directive = comment.strip_prefix('@').unwrap_or(comment).trim();
+        let Some(value) = directive.strip_prefix("known-bug:") else {
+            continue;
+        };
+
+        for reference in value.split(|c: char| c == ',' || c.is_whitespace()) {
+            if let Some(number) = parse_issue_reference(reference, repo) {
+                if !issues.contains(&number) {
+                    issues.push(number);
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// Parse a single issue reference from a `known-bug` directive
+fn parse_issue_reference(reference: &str, expected_repo: &str) -> Option<u64> {
+    let reference = reference.trim();
+
+    let number = if let Some(number) = reference.strip_prefix('#') {
+        number
+    } else if let Some((repo, number)) = reference.split_once('#') {
+        if repo != expected_repo {
+            return None;
+        }
+        number
+    } else {
+        let url = reference
+            .strip_prefix("https://")
+            .or_else(|| reference.strip_prefix("http://"))?;
+        let path = url.strip_prefix("github.com/")?;
+        let (repo, number) = path.split_once("/issues/")?;
+        if repo != expected_repo {
+            return None;
+        }
+        number.trim_end_matches('/')
+    };
+
+    number.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUST: &str = "rust-lang/rust";
+
+    #[test]
+    fn test_parse_known_bugs() {
+        assert_eq!(parse_known_bugs("//@ known-bug: #12345\nfn main() {}\n", CommentSyntax::Slashes, RUST), vec![12345]);
+        assert_eq!(parse_known_bugs("// known-bug: #12345\n", CommentSyntax::Slashes, RUST), vec![12345]);
+        assert_eq!(
+            parse_known_bugs("//@ known-bug: rust-lang/rust#12345\n", CommentSyntax::Slashes, RUST),
+            vec![12345]
+        );
+        assert_eq!(
+            parse_known_bugs("//@ known-bug: https://github.com/rust-lang/rust/issues/12345\n", CommentSyntax::Slashes, RUST),
+            vec![12345]
+        );
+        assert_eq!(
+            parse_known_bugs("//@ known-bug: #111\n//@ compile-flags: -Zfoo\n//@ known-bug: #222, #111\n", CommentSyntax::Slashes, RUST),
+            vec![111, 222]
+        );
+        assert_eq!(parse_known_bugs("//@ known-bug: unknown\n", CommentSyntax::Slashes, RUST), Vec::<u64>::new());
+        assert_eq!(
+            parse_known_bugs("//@ known-bug: rust-lang/chalk#12\n", CommentSyntax::Slashes, RUST),
+            Vec::<u64>::new()
+        );
+    }
+
+    #[test]
+    fn test_parse_known_bugs_other_repo() {
+        assert_eq!(
+            parse_known_bugs("//@ known-bug: rust-lang/rust-clippy#12\n", CommentSyntax::Slashes, "rust-lang/rust-clippy"),
+            vec![12]
+        );
+        assert_eq!(
+            parse_known_bugs("//@ known-bug: rust-lang/rust#12\n", CommentSyntax::Slashes, "rust-lang/rust-clippy"),
+            Vec::<u64>::new()
+        );
+    }
+
+    #[test]
+    fn test_parse_known_bugs_comment_syntax() {
+        assert_eq!(CommentSyntax::for_path("tests/crashes/12345.rs"), CommentSyntax::Slashes);
+        assert_eq!(CommentSyntax::for_path("tests/crashes/repro.sh"), CommentSyntax::Hash);
+        assert_eq!(CommentSyntax::for_path("tests/crashes/README.md"), CommentSyntax::Html);
+
+        assert_eq!(
+            parse_known_bugs("#!/bin/sh\n# known-bug: #12345\nrustc foo.rs\n", CommentSyntax::Hash, RUST),
+            vec![12345]
+        );
+        assert_eq!(
+            parse_known_bugs("<!-- known-bug: #12345 -->\n<!--known-bug: #222-->\n# Title\n", CommentSyntax::Html, RUST),
+            vec![12345, 222]
+        );
+        assert_eq!(
+            parse_known_bugs("// known-bug: #12345\n", CommentSyntax::Hash, RUST),
+            Vec::<u64>::new()
+        );
+    }
+
+    #[test]
+    fn test_parse_known_bugs_stops_at_code() {
+        assert_eq!(
+            parse_known_bugs("fn main() {}\n//@ known-bug: #12345\n", CommentSyntax::Slashes, RUST),
+            Vec::<u64>::new()
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use crate::progress::Progress;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Whether an issue is open or closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueState {
+    #[serde(alias = "OPEN")]
+    Open,
+    #[serde(alias = "CLOSED")]
+    Closed,
+}
+
+/// Why an issue was closed (or reopened)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateReason {
+    #[serde(alias = "COMPLETED")]
+    Completed,
+    #[serde(alias = "NOT_PLANNED")]
+    NotPlanned,
+    #[serde(alias = "DUPLICATE")]
+    Duplicate,
+    #[serde(alias = "REOPENED")]
+    Reopened,
+    /// A reason added to GitHub after this tool was written
+    #[serde(other)]
+    Other,
+}
+
+impl fmt::Display for StateReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            StateReason::Completed => "completed",
+            StateReason::NotPlanned => "not planned",
+            StateReason::Duplicate => "duplicate",
+            StateReason::Reopened => "reopened",
+            StateReason::Other => "other",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// What is known about an issue beyond its number
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IssueMetadata {
+    pub number: u64,
+    /// None for entries migrated from caches that only stored numbers
+    pub title: Option<String>,
+    pub state: IssueState,
+    pub state_reason: Option<StateReason>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl IssueMetadata {
+    /// An open issue known only by its number
+    pub fn open(number: u64) -> Self {
+        Self {
+            number,
+            title: None,
+            state: IssueState::Open,
+            state_reason: None,
+            labels: Vec::new(),
+            closed_at: None,
+            updated_at: None,
+        }
+    }
+}
+
+/// Issue states returned by a provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueStates {
+    /// State by issue number, possibly including issues that were not asked for
+    pub states: BTreeMap<u64, IssueState>,
+    /// Numbers known to be pull requests rather than issues
+    pub pull_requests: BTreeSet<u64>,
+    /// Titles, close reasons and labels, for the issues they are known of
+    pub issues: BTreeMap<u64, IssueMetadata>,
+    /// When the states were fetched from GitHub
+    pub fetched_at: DateTime<Utc>,
+    /// Whether the states come from stored data rather than a live lookup
+    pub cached: bool,
+}
+
+impl IssueStates {
+    /// States from a complete list of open issues and pull requests: every
+    /// other requested number is a closed issue
+    pub fn from_open_issues(
+        open: &HashSet<u64>,
+        pull_requests: &HashSet<u64>,
+        numbers: &[u64],
+        fetched_at: DateTime<Utc>,
+        cached: bool,
+    ) -> Self {
+        let mut states: BTreeMap<u64, IssueState> = open
+            .iter()
+            .map(|&number| (number, IssueState::Open))
+            .collect();
+        for &number in numbers {
+            if !pull_requests.contains(&number) {
+                states.entry(number).or_insert(IssueState::Closed);
+            }
+        }
+
+        Self {
+            states,
+            pull_requests: pull_requests.iter().copied().collect(),
+            issues: BTreeMap::new(),
+            fetched_at,
+            cached,
+        }
+    }
+
+    /// Numbers of the open issues
+    pub fn open(&self) -> HashSet<u64> {
+        self.states
+            .iter()
+            .filter(|(_, state)| **state == IssueState::Open)
+            .map(|(&number, _)| number)
+            .collect()
+    }
+}
+
+/// Source of issue states for the audit
+#[async_trait]
+pub trait IssueStateProvider: Send + Sync {
+    /// Look up the state of `numbers`, leaving out issues the provider knows nothing about
+    async fn issue_states(&self, numbers: &[u64]) -> Result<IssueStates>;
+}
+
+/// One line of a user-supplied issue state file
+#[derive(Debug, Deserialize)]
+struct IssueStateRecord {
+    number: u64,
+    state: IssueState,
+}
+
+/// Issue states from a user-supplied JSON or CSV file
+/// JSON files hold an array of `{"number": 12345, "state": "open"}` objects,
+/// CSV files a `number,state` header followed by one issue per line.
+#[derive(Debug, Clone)]
+pub struct FileProvider {
+    path: PathBuf,
+    progress: Progress,
+}
+
+impl FileProvider {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            progress: Progress::default(),
+        }
+    }
+
+    /// Where progress messages go (default: nowhere)
+    pub fn progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+}
+
+#[async_trait]
+impl IssueStateProvider for FileProvider {
+    async fn issue_states(&self, _numbers: &[u64]) -> Result<IssueStates> {
+        let path = &self.path;
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read issue state file {:?}", path))?;
+
+        let records = parse_issue_states(path, &contents)
+            .with_context(|| format!("Failed to parse issue state file {:?}", path))?;
+
+        // The file is as fresh as its last modification
+        let fetched_at = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now());
+
+        self.progress
+            .message(format!("Using issue states from {} ({} issues)\n", path.display(), records.len()));
+
+        Ok(IssueStates {
+            states: records.into_iter().map(|r| (r.number, r.state)).collect(),
+            pull_requests: BTreeSet::new(),
+            issues: BTreeMap::new(),
+            fetched_at,
+            cached: true,
+        })
+    }
+}
+
+/// Parse an issue state file, picking the format from its extension
+fn parse_issue_states(path: &Path, contents: &str) -> Result<Vec<IssueStateRecord>> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => Ok(serde_json::from_str(contents)?),
+        Some("csv") => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(contents.as_bytes());
+            reader
+                .deserialize()
+                .collect::<Result<_, _>>()
+                .map_err(Into::into)
+        }
+        _ => anyhow::bail!("Unsupported issue state file, expected a .json or .csv extension"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn states(records: Vec<IssueStateRecord>) -> Vec<(u64, IssueState)> {
+        records.into_iter().map(|r| (r.number, r.state)).collect()
+    }
+
+    #[test]
+    fn test_parse_issue_states() {
+        let expected = vec![(100, IssueState::Open), (200, IssueState::Closed)];
+
+        let json = r#"[{"number": 100, "state": "open"}, {"number": 200, "state": "closed"}]"#;
+        let records = parse_issue_states(Path::new("states.json"), json).unwrap();
+        assert_eq!(states(records), expected);
+
+        let csv = "number,state\n100, open\n200,closed\n";
+        let records = parse_issue_states(Path::new("states.csv"), csv).unwrap();
+        assert_eq!(states(records), expected);
+
+        assert!(parse_issue_states(Path::new("states.csv"), "number,state\n100,reopened\n").is_err());
+        assert!(parse_issue_states(Path::new("states.txt"), "").is_err());
+    }
+
+    #[test]
+    fn test_from_open_issues() {
+        let open: HashSet<u64> = [100, 300].into_iter().collect();
+        let pull_requests: HashSet<u64> = [400].into_iter().collect();
+        let issue_states = IssueStates::from_open_issues(&open, &pull_requests, &[100, 200, 400], Utc::now(), false);
+
+        assert_eq!(
+            issue_states.states.into_iter().collect::<Vec<_>>(),
+            vec![(100, IssueState::Open), (200, IssueState::Closed), (300, IssueState::Open)]
+        );
+        assert_eq!(issue_states.pull_requests.into_iter().collect::<Vec<_>>(), vec![400]);
+    }
+}
@@ -0,0 +1,21 @@
+//! Audit a Rust repository for crash tests that were deleted while their
+//! issue is still open.
+//!
+//! [`Auditor`] runs the whole audit and returns an [`AuditResult`]; the
+//! modules below expose the individual steps for other front-ends.
+
+mod audit;
+pub mod baseline;
+pub mod cache;
+pub mod config;
+pub mod diff;
+pub mod git;
+pub mod github;
+pub mod header;
+pub mod issues;
+pub mod model;
+pub mod pr;
+pub mod progress;
+pub mod report;
+
+pub use audit::{categorize, closed_candidates, AuditResult, Auditor, OpenIssueSource};
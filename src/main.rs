@@ -1,23 +1,29 @@
-mod cache;
-mod git;
-mod github;
-mod report;
-
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use chrono::NaiveDate;
-use std::path::PathBuf;
+use rust_crash_audit::github::{GitHubProvider, GitHubRepo};
+use rust_crash_audit::issues::{IssueState, IssueStateProvider};
+use rust_crash_audit::progress::Progress;
+use rust_crash_audit::{baseline, cache, config, diff, git, github, model, pr, report, Auditor, OpenIssueSource};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
 #[derive(Parser, Debug)]
 #[command(
     name = "rust-crash-audit",
     about = "Audit Rust repository for out-of-sync crash test files and issues",
-    version
+    version,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to the Rust repository
-    #[arg(value_name = "REPO_PATH")]
-    repo_path: PathBuf,
+    #[arg(value_name = "REPO_PATH", required = true)]
+    repo_path: Option<PathBuf>,
 
     /// Start date for scanning (format: YYYY-MM-DD)
     #[arg(long, value_name = "DATE")]
@@ -27,189 +33,527 @@ struct Args {
     #[arg(long, value_name = "DATE")]
     to: Option<NaiveDate>,
 
+    /// GitHub repository owner (default: inferred from the `origin` remote)
+    #[arg(long, value_name = "OWNER")]
+    owner: Option<String>,
+
+    /// GitHub repository name (default: inferred from the `origin` remote)
+    #[arg(long, value_name = "REPO")]
+    repo: Option<String>,
+
     /// GitHub personal access token (or use GITHUB_TOKEN env var)
     #[arg(long, value_name = "TOKEN", env = "GITHUB_TOKEN")]
     github_token: Option<String>,
 
-    /// Force refresh the cache (ignore existing cache)
+    /// Also report crash tests still present whose issue is already closed
+    #[arg(long)]
+    check_existing: bool,
+
+    /// Commit message formats to recognize PR numbers from, in priority order
+    /// [default: bors,github-merge,rollup-merge,squash]
+    #[arg(long, value_name = "FORMAT", value_enum, value_delimiter = ',')]
+    pr_formats: Option<Vec<pr::PrRecognizer>>,
+
+    /// Config file with watched directories (default: .crash-audit.toml if present)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Number of worker threads for the history scan (default: available CPUs)
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<usize>,
+
+    /// Ignore the saved scan checkpoint and walk the full history again
+    #[arg(long)]
+    rescan: bool,
+
+    /// Refresh the cache with the issues changed since it was written
     #[arg(long)]
     refresh_cache: bool,
 
+    /// Refresh the cache by listing every open issue again (implies --refresh-cache)
+    #[arg(long)]
+    full_refresh: bool,
+
+    /// Directory for the issue cache and scan checkpoints
+    /// [default: $XDG_CACHE_HOME/rust-crash-audit or ~/.cache/rust-crash-audit]
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// Refresh the cache automatically once it is older than this, e.g. 6h or 2d
+    /// (overrides max_cache_age in the config file)
+    #[arg(long, value_name = "AGE", value_parser = cache::parse_duration)]
+    max_cache_age: Option<Duration>,
+
+    /// How to look up issue states without a cache: batched GraphQL queries for
+    /// just the referenced issues, or listing every open issue
+    #[arg(long, value_name = "MODE", value_enum, default_value_t = github::IssueLookup::Auto)]
+    issue_lookup: github::IssueLookup,
+
+    /// Never use the network, only the issue cache or --issue-states
+    #[arg(long, conflicts_with_all = ["refresh_cache", "full_refresh", "check_existing"])]
+    offline: bool,
+
+    /// Read issue states from a JSON or CSV file instead of GitHub
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["refresh_cache", "full_refresh"])]
+    issue_states: Option<PathBuf>,
+
+    /// Report format, progress messages go to stderr
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t = report::OutputFormat::Text)]
+    format: report::OutputFormat,
+
+    /// Also save the JSON report to FILE, e.g. for a later `diff`
+    #[arg(long, value_name = "FILE")]
+    save_report: Option<PathBuf>,
+
+    /// Write the report to FILE instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Also list closed issues whose tests were all deleted (Markdown report)
+    #[arg(long)]
+    include_synced: bool,
+
+    /// Baseline file of known findings to suppress (default: .crash-audit-baseline.toml)
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Add the current findings to the baseline file
+    #[arg(long)]
+    update_baseline: bool,
+
+    /// Findings that make the process exit with status 1
+    #[arg(long, value_name = "KIND", value_enum, default_value_t = model::FailOn::OutOfSync)]
+    fail_on: model::FailOn,
+
+    /// Number of findings tolerated before failing
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    fail_threshold: usize,
+
     /// Verbose output
     #[arg(short, long)]
     verbose: bool,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Load .env file if it exists (optional)
-    let _ = dotenvy::dotenv();
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show what changed between two saved JSON reports
+    Diff {
+        /// Earlier report
+        old: PathBuf,
+        /// Later report
+        new: PathBuf,
+        /// Print the changes as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Inspect and manage the issue cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
 
-    let args = Args::parse();
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    /// Show where the cache is, how old it is and what it holds
+    Status {
+        #[command(flatten)]
+        target: CacheTarget,
+    },
+    /// Fetch the issues changed since the cache was written
+    Refresh {
+        #[command(flatten)]
+        target: CacheTarget,
+        /// List every open issue again instead of only the changes
+        #[arg(long)]
+        full: bool,
+        /// GitHub personal access token (or use GITHUB_TOKEN env var)
+        #[arg(long, value_name = "TOKEN", env = "GITHUB_TOKEN")]
+        github_token: Option<String>,
+    },
+    /// Delete the cached issues (of every repository unless --owner/--repo is given)
+    Clear {
+        #[command(flatten)]
+        target: CacheTarget,
+    },
+    /// Write the cached issues to a file, e.g. to share a warm cache between CI runners
+    Export {
+        /// File to write
+        file: PathBuf,
+        #[command(flatten)]
+        target: CacheTarget,
+    },
+    /// Replace the cached issues with a file written by `cache export`
+    Import {
+        /// File to read
+        file: PathBuf,
+        #[command(flatten)]
+        target: CacheTarget,
+    },
+    /// Show what the cache knows about one issue
+    Query {
+        /// Issue number
+        number: u64,
+        #[command(flatten)]
+        target: CacheTarget,
+    },
+}
 
-    // Validate repository path
-    if !args.repo_path.exists() {
-        anyhow::bail!("Repository path does not exist: {:?}", args.repo_path);
-    }
+/// Which cache the `cache` subcommands work on
+#[derive(clap::Args, Debug)]
+struct CacheTarget {
+    /// GitHub repository owner (default: inferred from the `origin` remote of the current directory)
+    #[arg(long, value_name = "OWNER")]
+    owner: Option<String>,
+
+    /// GitHub repository name (default: inferred from the `origin` remote of the current directory)
+    #[arg(long, value_name = "REPO")]
+    repo: Option<String>,
+
+    /// Directory for the issue cache and scan checkpoints
+    /// [default: $XDG_CACHE_HOME/rust-crash-audit or ~/.cache/rust-crash-audit]
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+}
 
-    if !args.repo_path.is_dir() {
-        anyhow::bail!("Repository path is not a directory: {:?}", args.repo_path);
+impl CacheTarget {
+    fn cache_dir(&self) -> PathBuf {
+        self.cache_dir.clone().unwrap_or_else(cache::default_cache_dir)
     }
 
-    // Validate date range
-    if let (Some(from), Some(to)) = (args.from, args.to) {
-        if from > to {
-            anyhow::bail!("Start date must be before end date");
+    /// The repository asked for, if any
+    fn explicit_repo(&self) -> Option<GitHubRepo> {
+        if self.owner.is_none() && self.repo.is_none() {
+            return None;
         }
+        Some(self.repo())
     }
 
-    println!("Scanning Rust repository...");
-    if let Some(from) = args.from {
-        print!("Date range: {} to ", from);
-        if let Some(to) = args.to {
-            println!("{}", to);
-        } else {
-            println!("present");
+    /// The repository asked for, with parts left out inferred from the `origin`
+    /// remote like the audit does, then rust-lang/rust
+    fn repo(&self) -> GitHubRepo {
+        if let (Some(owner), Some(repo)) = (&self.owner, &self.repo) {
+            return GitHubRepo::new(owner, repo);
         }
+
+        let inferred = git::origin_url(Path::new("."))
+            .ok()
+            .flatten()
+            .and_then(|url| GitHubRepo::from_remote_url(&url));
+        let default = inferred.unwrap_or_else(|| {
+            eprintln!("Could not infer GitHub repository from origin remote, defaulting to rust-lang/rust");
+            GitHubRepo::default()
+        });
+        GitHubRepo::new(
+            self.owner.clone().unwrap_or(default.owner),
+            self.repo.clone().unwrap_or(default.name),
+        )
     }
-    println!();
+}
 
-    // Scan git history for deleted crash test files
-    let deleted_files = git::scan_deleted_crash_tests(&args.repo_path, args.from, args.to)
-        .context("Failed to scan git history")?;
+/// Exit code when the audit found more findings than allowed
+const EXIT_FINDINGS: u8 = 1;
+/// Exit code for operational errors (git, GitHub, cache, I/O)
+const EXIT_ERROR: u8 = 2;
 
-    println!("Found {} deleted crash test files\n", deleted_files.len());
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Load .env file if it exists (optional)
+    let _ = dotenvy::dotenv();
 
-    if deleted_files.is_empty() {
-        println!("No deleted crash test files found in the specified range.");
-        return Ok(());
-    }
+    let args = Args::parse();
+
+    let result = match args.command {
+        Some(Command::Diff { old, new, json }) => run_diff(&old, &new, json),
+        Some(Command::Cache { command }) => run_cache(command).await,
+        None => run(args).await,
+    };
 
-    // Load or fetch open issues (with caching)
-    let open_issues = if args.refresh_cache {
-        // Force refresh: fetch from API and save to cache
-        if args.verbose {
-            println!("Refreshing cache...\n");
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::from(EXIT_ERROR)
         }
-        let issues = github::fetch_all_open_issues(args.github_token.clone(), args.verbose)
-            .await
-            .context("Failed to fetch open issues from GitHub")?;
+    }
+}
 
-        cache::save_cache(&issues)
-            .context("Failed to save cache")?;
+/// Run the audit, returning the exit code for the findings
+async fn run(args: Args) -> Result<ExitCode> {
+    let repo_path = args.repo_path.clone().expect("REPO_PATH is required without a subcommand");
 
-        if !args.verbose {
-            println!("Cached {} open issues\n", issues.len());
-        }
+    let config = config::load_config(args.config.as_deref())?;
+    let baseline_path = args
+        .baseline
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(baseline::DEFAULT_BASELINE_FILE));
 
-        issues
-    } else if cache::cache_exists() {
-        // Load from cache
-        let cached = cache::load_cache()
-            .context("Failed to load cache")?;
+    let issue_source = match &args.issue_states {
+        Some(path) => OpenIssueSource::File(path.clone()),
+        None if args.offline => OpenIssueSource::Offline,
+        None => OpenIssueSource::GitHub { refresh: args.refresh_cache || args.full_refresh },
+    };
+    let uses_github = matches!(issue_source, OpenIssueSource::GitHub { .. });
 
-        let age = cache::format_duration(cached.age());
-        println!("Using cached data (updated {} ago)", age);
-        println!("Use --refresh-cache to update\n");
+    if args.verbose && uses_github && args.github_token.is_none() {
+        eprintln!("Note: Using unauthenticated API (60 requests/hour limit)");
+        eprintln!("Set GITHUB_TOKEN environment variable for higher limits (5,000 requests/hour)");
+        eprintln!();
+    }
 
-        cached.to_hashset()
-    } else {
-        // No cache: fetch from API and save to cache
-        let issues = github::fetch_all_open_issues(args.github_token.clone(), args.verbose)
-            .await
-            .context("Failed to fetch open issues from GitHub")?;
+    // Command line PR formats win over the config file
+    let mut auditor = Auditor::new(&repo_path);
+    if let Some(cache_dir) = &args.cache_dir {
+        auditor = auditor.cache_dir(cache_dir);
+    }
+    let result = auditor
+        .date_range(args.from, args.to)
+        .github_repo(args.owner.clone(), args.repo.clone())
+        .github_token(args.github_token.clone())
+        .config(config)
+        .pr_formats(args.pr_formats.clone())
+        .issue_source(issue_source)
+        .issue_lookup(args.issue_lookup)
+        .full_refresh(args.full_refresh)
+        .max_cache_age(args.max_cache_age)
+        .jobs(args.jobs)
+        .rescan(args.rescan)
+        .check_existing(args.check_existing)
+        .baseline(&baseline_path, args.update_baseline)
+        .progress(Progress::stderr())
+        .verbose(args.verbose)
+        .run()
+        .await?;
+
+    let refreshed = args.refresh_cache || args.full_refresh;
+    if uses_github && !refreshed && result.report.metadata.cache_age_seconds.is_some() {
+        eprintln!("Issue states came from the cache, use --refresh-cache to update\n");
+    }
 
-        cache::save_cache(&issues)
-            .context("Failed to save cache")?;
+    for entry in &result.expired_baseline_entries {
+        eprintln!(
+            "Warning: baseline entry for {} expired on {} ({})",
+            entry.describe(),
+            entry.expiry_date().map(|d| d.to_string()).unwrap_or_default(),
+            entry.reason
+        );
+    }
+    if let Some(added) = result.baseline_entries_added {
+        eprintln!("Added {} entries to {}\n", added, baseline_path.display());
+    }
 
-        if !args.verbose {
-            println!("Cached {} open issues\n", issues.len());
-        }
+    // Other formats and report files always get a (possibly empty) report
+    if result.deleted_files.is_empty()
+        && !args.check_existing
+        && args.format == report::OutputFormat::Text
+        && args.output.is_none()
+        && args.save_report.is_none()
+    {
+        eprintln!("No deleted crash test files found in the specified range.");
+        return Ok(ExitCode::SUCCESS);
+    }
 
-        issues
-    };
+    let audit = result.report;
 
-    // Get current crash test files to detect partial deletions
-    let current_files = git::get_current_crash_test_files(&args.repo_path)
-        .context("Failed to scan current crash test files")?;
-
-    // Group deleted files by issue number
-    let mut files_by_issue: std::collections::HashMap<u64, Vec<&git::DeletedCrashTest>> =
-        std::collections::HashMap::new();
-    for file in &deleted_files {
-        files_by_issue
-            .entry(file.issue_number)
-            .or_insert_with(Vec::new)
-            .push(file);
-    }
-
-    // Categorize issues
-    let mut fully_deleted_out_of_sync = Vec::new();
-    let mut fully_deleted_synced = Vec::new();
-    let mut partially_deleted = Vec::new();
-    let mut files_with_open_issues = 0;
-    let mut files_with_closed_issues = 0;
-
-    println!("Checking deleted files against open issues...");
-    for (issue_number, files) in files_by_issue {
-        // Count how many files for this issue still exist
-        let remaining_count = current_files.iter().filter(|filename| {
-            // Extract issue number from current filename
-            if let Some(current_issue) = git::extract_issue_number_from_filename(filename) {
-                current_issue == issue_number
-            } else {
-                false
-            }
-        }).count();
-
-        // Count files for statistics
-        let file_count = files.len();
-        if open_issues.contains(&issue_number) {
-            files_with_open_issues += file_count;
-        } else {
-            files_with_closed_issues += file_count;
+    // Generate report
+    let options = report::RenderOptions {
+        include_synced: args.include_synced,
+    };
+    let rendered = report::render(&audit, args.format, options)?;
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, rendered)
+                .with_context(|| format!("Failed to write report to {:?}", path))?;
+            eprintln!("Report written to {}", path.display());
         }
+        None => print!("{}", rendered),
+    }
+
+    if let Some(path) = &args.save_report {
+        let json = report::render(&audit, report::OutputFormat::Json, options)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to save report to {:?}", path))?;
+    }
+
+    let findings = audit.finding_count(args.fail_on);
+    if findings > args.fail_threshold {
+        eprintln!(
+            "Failing: {} finding(s) for --fail-on {} (threshold {})",
+            findings,
+            args.fail_on.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default(),
+            args.fail_threshold
+        );
+        return Ok(ExitCode::from(EXIT_FINDINGS));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Compare two saved reports
+fn run_diff(old_path: &Path, new_path: &Path, json: bool) -> Result<ExitCode> {
+    let old = diff::load_report(old_path)?;
+    let new = diff::load_report(new_path)?;
+
+    if old.metadata.repository != new.metadata.repository {
+        anyhow::bail!(
+            "Reports are for different repositories ({} and {})",
+            old.metadata.repository,
+            new.metadata.repository
+        );
+    }
+
+    let changes = diff::diff_reports(&old, &new);
+    if json {
+        let output = serde_json::to_string_pretty(&changes)
+            .context("Failed to serialize diff")?;
+        println!("{}", output);
+    } else {
+        let mut output = String::new();
+        diff::write_text(&mut output, &changes, &new)
+            .context("Failed to render diff")?;
+        print!("{}", output);
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
 
-        if remaining_count > 0 {
-            // Partial deletion - some files remain
-            partially_deleted.push((issue_number, files, remaining_count));
-            if args.verbose {
-                println!(
-                    "  ℹ️  Issue #{}: {} file(s) deleted, {} remain",
-                    issue_number,
-                    file_count,
-                    remaining_count
-                );
+/// Run a `cache` subcommand
+async fn run_cache(command: CacheCommand) -> Result<ExitCode> {
+    match command {
+        CacheCommand::Status { target } => {
+            let cache_dir = target.cache_dir();
+            println!("Cache directory: {}", cache_dir.display());
+            let checkpoints = cache::load_scan_checkpoints(&cache_dir)?;
+            println!("Scan checkpoints: {}", checkpoints.len());
+
+            let repos = match target.explicit_repo() {
+                Some(github_repo) => vec![github_repo],
+                None => cache::cached_repos(&cache_dir)?,
+            };
+            if repos.is_empty() {
+                println!("\nNo cached issues");
             }
-        } else {
-            // Full deletion - all files for this issue are gone
-            if open_issues.contains(&issue_number) {
-                // Issue is still open - this is out of sync!
-                if args.verbose {
-                    println!("  ⚠️  Issue #{} is still OPEN (all files deleted)", issue_number);
-                }
-                fully_deleted_out_of_sync.push((issue_number, files));
-            } else {
-                // Issue is closed or doesn't exist - this is expected
-                if args.verbose {
-                    println!("  ✅ Issue #{} is closed (all files deleted)", issue_number);
-                }
-                fully_deleted_synced.push((issue_number, files));
+            for github_repo in repos {
+                println!();
+                print_cache_status(&cache_dir, &github_repo)?;
             }
         }
+        CacheCommand::Refresh { target, full, github_token } => {
+            let github_repo = target.repo();
+            // Only the listing is written to the cache, batched lookups are not
+            let provider = GitHubProvider::new(github_repo.clone(), github_token)
+                .progress(Progress::stderr())
+                .lookup(github::IssueLookup::List)
+                .full_refresh(full)
+                .cache_dir(target.cache_dir());
+            provider.issue_states(&[]).await?;
+            print_cache_status(&target.cache_dir(), &github_repo)?;
+        }
+        CacheCommand::Clear { target } => {
+            let cache_dir = target.cache_dir();
+            let removed = cache::clear_cache(&cache_dir, target.explicit_repo().as_ref())?;
+            println!("Removed {} file(s) from {}", removed, cache_dir.display());
+        }
+        CacheCommand::Export { file, target } => {
+            let github_repo = target.repo();
+            let cached = cache::export_cache(&target.cache_dir(), &github_repo, &file, &Progress::stderr())?;
+            println!(
+                "Exported {} issues of {} (updated {} ago) to {}",
+                cached.issues.len(),
+                github_repo,
+                cache::format_duration(cached.age()),
+                file.display()
+            );
+        }
+        CacheCommand::Import { file, target } => {
+            let github_repo = target.repo();
+            let cached = cache::import_cache(&target.cache_dir(), &github_repo, &file)?;
+            println!(
+                "Imported {} issues of {} (updated {} ago) from {}",
+                cached.issues.len(),
+                github_repo,
+                cache::format_duration(cached.age()),
+                file.display()
+            );
+        }
+        CacheCommand::Query { number, target } => {
+            let github_repo = target.repo();
+            let cached = cache::load_cache(&target.cache_dir(), &github_repo, &Progress::stderr())
+                .with_context(|| format!("Failed to load the cache of {}", github_repo))?;
+            print_cached_issue(&cached, number);
+        }
     }
 
-    println!();
+    Ok(ExitCode::SUCCESS)
+}
 
-    // Generate report
-    report::print_report(
-        &fully_deleted_out_of_sync,
-        &fully_deleted_synced,
-        &partially_deleted,
-        files_with_open_issues,
-        files_with_closed_issues,
-        open_issues.len(),
-    );
+/// Print what a repository's cache holds
+fn print_cache_status(cache_dir: &Path, github_repo: &GitHubRepo) -> Result<()> {
+    println!("{}", github_repo);
+    if !cache::cache_exists(cache_dir, github_repo) {
+        println!("  No cached issues");
+        return Ok(());
+    }
 
+    let cached = cache::load_cache(cache_dir, github_repo, &Progress::stderr())
+        .context("Failed to load cache")?;
+    let open = cached.to_hashset().len();
+    println!("  File: {}", cache::cache_path(cache_dir, github_repo).display());
+    println!("  Schema version: {}", cached.schema_version);
+    println!(
+        "  Updated: {} ({} ago)",
+        cached.timestamp.format("%Y-%m-%d %H:%M UTC"),
+        cache::format_duration(cached.age())
+    );
+    match cached.last_full_sync {
+        Some(last_full_sync) => println!("  Last full sync: {}", last_full_sync.format("%Y-%m-%d %H:%M UTC")),
+        None => println!("  Last full sync: unknown"),
+    }
+    println!("  Open issues: {}", open);
+    println!("  Closed issues: {}", cached.issues.len() - open);
+    match &cached.pull_request_numbers {
+        Some(pull_requests) => println!("  Pull requests: {}", pull_requests.len()),
+        None => println!("  Pull requests: unknown (cache predates pull request detection)"),
+    }
     Ok(())
 }
+
+/// Print what the cache knows about one issue
+fn print_cached_issue(cached: &cache::CachedIssues, number: u64) {
+    let updated = cache::format_duration(cached.age());
+
+    if cached.pull_requests().contains(&number) {
+        println!("#{} is a pull request (cache updated {} ago)", number, updated);
+        return;
+    }
+    let Some(issue) = cached.issue(number) else {
+        println!(
+            "#{} is not in the cache, so it counts as closed (cache updated {} ago)",
+            number, updated
+        );
+        return;
+    };
+
+    println!("#{} {}", issue.number, issue.title.as_deref().unwrap_or("(title unknown)"));
+    let state = match issue.state {
+        IssueState::Open => "open".to_string(),
+        IssueState::Closed => {
+            let mut state = "closed".to_string();
+            if let Some(reason) = issue.state_reason {
+                state.push_str(&format!(" as {}", reason));
+            }
+            if let Some(closed_at) = issue.closed_at {
+                state.push_str(&format!(" on {}", closed_at.format("%Y-%m-%d %H:%M UTC")));
+            }
+            state
+        }
+    };
+    println!("  State: {}", state);
+    if !issue.labels.is_empty() {
+        println!("  Labels: {}", issue.labels.join(", "));
+    }
+    if let Some(updated_at) = issue.updated_at {
+        println!("  Last updated on GitHub: {}", updated_at.format("%Y-%m-%d %H:%M UTC"));
+    }
+    println!("  Cache updated {} ago", updated);
+}
@@ -0,0 +1,532 @@
+use crate::git::{DeletedCrashTest, IssueRef};
+use crate::github::GitHubRepo;
+use crate::issues::StateReason;
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Version of the serialized report layout
+/// Bumped whenever a field is removed, renamed or changes meaning.
+/// Adding a field does not bump the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Full result of an audit run, shared by all output formats
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditReport {
+    pub schema_version: u32,
+    pub metadata: RunMetadata,
+    /// Issues still open although all their crash tests are gone
+    pub out_of_sync: Vec<IssueDeletions>,
+    /// Issues with some crash tests deleted and others remaining
+    pub partially_deleted: Vec<IssueDeletions>,
+    /// Issues closed (or missing) with all their crash tests gone
+    pub synced: Vec<IssueDeletions>,
+    /// Closed issues whose crash tests are still present (None unless --check-existing)
+    pub closed_with_tests: Option<Vec<ClosedWithTests>>,
+    /// Files whose filename and `known-bug` directives disagree
+    pub issue_mismatches: Vec<IssueMismatch>,
+    /// Crash tests whose number refers to a pull request instead of an issue
+    #[serde(default)]
+    pub pull_request_references: Vec<PullRequestReference>,
+    /// Findings hidden by the baseline file
+    #[serde(default)]
+    pub suppressed: Vec<SuppressedFinding>,
+    pub statistics: Statistics,
+}
+
+/// Categorized findings a report is built from
+#[derive(Debug, Clone, Default)]
+pub struct Findings {
+    pub out_of_sync: Vec<IssueDeletions>,
+    pub partially_deleted: Vec<IssueDeletions>,
+    pub synced: Vec<IssueDeletions>,
+    pub closed_with_tests: Option<Vec<ClosedWithTests>>,
+    pub issue_mismatches: Vec<IssueMismatch>,
+    pub pull_request_references: Vec<PullRequestReference>,
+    pub suppressed: Vec<SuppressedFinding>,
+}
+
+/// Information about the audit run itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunMetadata {
+    pub tool_version: String,
+    pub generated_at: DateTime<Utc>,
+    pub repository: GitHubRepo,
+    /// Commit the history scan ended at
+    pub head_sha: String,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// When the open issue list was fetched from GitHub
+    pub issues_fetched_at: DateTime<Utc>,
+    /// Age of the cached issue list in seconds (None if fetched during this run)
+    pub cache_age_seconds: Option<u64>,
+    /// Maximum cache age from `--max-cache-age` or the config file, in seconds
+    #[serde(default)]
+    pub cache_max_age_seconds: Option<u64>,
+    /// Whether the cached issue list was older than the maximum age and
+    /// could not be refreshed
+    #[serde(default)]
+    pub cache_stale: bool,
+}
+
+/// Deleted crash tests for a single issue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IssueDeletions {
+    pub issue_number: u64,
+    pub issue_open: bool,
+    /// Issue title, if the issue cache or lookup had it
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Why the issue was closed, if known
+    #[serde(default)]
+    pub state_reason: Option<StateReason>,
+    pub files: Vec<DeletedCrashTest>,
+    /// Crash tests for the issue still present in the tree
+    pub remaining_files: usize,
+}
+
+/// A closed issue whose crash tests are still present
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClosedWithTests {
+    pub issue_number: u64,
+    pub files: Vec<String>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub closing_pr: Option<u64>,
+}
+
+/// A crash test whose filename and `known-bug` directives disagree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IssueMismatch {
+    pub file_path: String,
+    pub issues: Vec<IssueRef>,
+}
+
+/// Crash tests referencing a number that is a pull request, not an issue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PullRequestReference {
+    pub pr_number: u64,
+    /// Paths of deleted and current crash tests referencing it
+    pub files: Vec<String>,
+}
+
+/// Kind of finding, as used for suppression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FindingCategory {
+    OutOfSync,
+    Partial,
+    Stale,
+    Mismatch,
+    PullRequest,
+}
+
+impl FindingCategory {
+    pub fn name(self) -> &'static str {
+        match self {
+            FindingCategory::OutOfSync => "out of sync",
+            FindingCategory::Partial => "partial cleanup",
+            FindingCategory::Stale => "stale tests",
+            FindingCategory::Mismatch => "issue mismatch",
+            FindingCategory::PullRequest => "pull request reference",
+        }
+    }
+}
+
+/// A finding hidden by a baseline entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuppressedFinding {
+    pub category: FindingCategory,
+    pub issue_number: Option<u64>,
+    /// Files involved (file names for stale tests, paths otherwise)
+    pub files: Vec<String>,
+    pub reason: String,
+    pub expires: Option<NaiveDate>,
+}
+
+/// Counters shown in the statistics block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Statistics {
+    /// Distinct deleted file paths
+    pub files_deleted: usize,
+    /// Deleted files referencing at least one open issue
+    pub files_with_open_issues: usize,
+    /// Deleted files whose issues are all closed
+    pub files_with_closed_issues: usize,
+    pub files_moved: usize,
+    pub files_deleted_outright: usize,
+    /// Open issues in the repository
+    pub total_open_issues: usize,
+    pub issues_out_of_sync: usize,
+    pub issues_partially_deleted: usize,
+    pub issues_synced: usize,
+    pub issue_mismatches: usize,
+    #[serde(default)]
+    pub pull_request_references: usize,
+    pub closed_issues_with_tests: Option<usize>,
+    pub stale_test_files: Option<usize>,
+    #[serde(default)]
+    pub findings_suppressed: usize,
+    /// Per watch group counts, in order of first appearance
+    pub groups: Vec<GroupStatistics>,
+}
+
+/// Counters for a single watch group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupStatistics {
+    pub name: String,
+    pub files_deleted: usize,
+    pub issues_out_of_sync: usize,
+    pub issues_partially_deleted: usize,
+}
+
+/// Which findings make the audit fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FailOn {
+    /// Open issues whose crash tests are all gone
+    OutOfSync,
+    /// Issues with some crash tests deleted and others remaining
+    Partial,
+    /// Any finding: out of sync, partial cleanup, stale tests, issue mismatches
+    /// or pull request references
+    Any,
+}
+
+impl AuditReport {
+    /// Assemble a report from categorized findings, sorting them and computing statistics
+    pub fn new(metadata: RunMetadata, findings: Findings, total_open_issues: usize) -> Self {
+        let Findings {
+            mut out_of_sync,
+            mut partially_deleted,
+            mut synced,
+            closed_with_tests,
+            issue_mismatches,
+            mut pull_request_references,
+            suppressed,
+        } = findings;
+        out_of_sync.sort_by_key(|issue| issue.issue_number);
+        partially_deleted.sort_by_key(|issue| issue.issue_number);
+        synced.sort_by_key(|issue| issue.issue_number);
+        pull_request_references.sort_by_key(|reference| reference.pr_number);
+
+        // A file referencing several issues shows up under each of them but is counted once:
+        // with the open issues if any of its issues is open, with the closed ones otherwise
+        let mut files: BTreeMap<&str, (bool, bool)> = BTreeMap::new();
+        for issue in out_of_sync.iter().chain(&partially_deleted).chain(&synced) {
+            for file in &issue.files {
+                let (open, moved) = files.entry(&file.file_path).or_default();
+                *open |= issue.issue_open;
+                *moved |= file.is_moved();
+            }
+        }
+        let files_deleted = files.len();
+        let files_with_open_issues = files.values().filter(|(open, _)| *open).count();
+        let files_with_closed_issues = files_deleted - files_with_open_issues;
+        let files_moved = files.values().filter(|(_, moved)| *moved).count();
+
+        let statistics = Statistics {
+            files_deleted,
+            files_with_open_issues,
+            files_with_closed_issues,
+            files_moved,
+            files_deleted_outright: files_deleted - files_moved,
+            total_open_issues,
+            issues_out_of_sync: out_of_sync.len(),
+            issues_partially_deleted: partially_deleted.len(),
+            issues_synced: synced.len(),
+            issue_mismatches: issue_mismatches.len(),
+            pull_request_references: pull_request_references.len(),
+            closed_issues_with_tests: closed_with_tests.as_ref().map(|closed| closed.len()),
+            stale_test_files: closed_with_tests
+                .as_ref()
+                .map(|closed| closed.iter().map(|c| c.files.len()).sum()),
+            findings_suppressed: suppressed.len(),
+            groups: group_statistics(&out_of_sync, &partially_deleted, &synced),
+        };
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            metadata,
+            out_of_sync,
+            partially_deleted,
+            synced,
+            closed_with_tests,
+            issue_mismatches,
+            pull_request_references,
+            suppressed,
+            statistics,
+        }
+    }
+
+    /// Number of findings of the given kind
+    pub fn finding_count(&self, fail_on: FailOn) -> usize {
+        match fail_on {
+            FailOn::OutOfSync => self.out_of_sync.len(),
+            FailOn::Partial => self.partially_deleted.len(),
+            FailOn::Any => {
+                self.out_of_sync.len()
+                    + self.partially_deleted.len()
+                    + self.closed_with_tests.as_ref().map_or(0, |closed| closed.len())
+                    + self.issue_mismatches.len()
+                    + self.pull_request_references.len()
+            }
+        }
+    }
+}
+
+/// Count deleted files and affected issues per watch group
+fn group_statistics(
+    out_of_sync: &[IssueDeletions],
+    partially_deleted: &[IssueDeletions],
+    synced: &[IssueDeletions],
+) -> Vec<GroupStatistics> {
+    let all_issues = out_of_sync
+        .iter()
+        .map(|issue| (issue, 1, 0))
+        .chain(partially_deleted.iter().map(|issue| (issue, 0, 1)))
+        .chain(synced.iter().map(|issue| (issue, 0, 0)));
+
+    let mut groups: Vec<GroupStatistics> = Vec::new();
+    let mut counted_files: HashSet<&str> = HashSet::new();
+    for (issue, out_of_sync, partial) in all_issues {
+        let mut seen: Vec<&str> = Vec::new();
+        for file in &issue.files {
+            let index = match groups.iter().position(|group| group.name == file.group) {
+                Some(index) => index,
+                None => {
+                    groups.push(GroupStatistics {
+                        name: file.group.clone(),
+                        files_deleted: 0,
+                        issues_out_of_sync: 0,
+                        issues_partially_deleted: 0,
+                    });
+                    groups.len() - 1
+                }
+            };
+            if counted_files.insert(&file.file_path) {
+                groups[index].files_deleted += 1;
+            }
+            if !seen.contains(&file.group.as_str()) {
+                seen.push(&file.group);
+                groups[index].issues_out_of_sync += out_of_sync;
+                groups[index].issues_partially_deleted += partial;
+            }
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::git::{DeletionKind, IssueSource};
+    use crate::pr::PrRecognizer;
+    use chrono::TimeZone;
+
+    fn deleted(path: &str, issue: u64, sha: &str, kind: DeletionKind) -> DeletedCrashTest {
+        DeletedCrashTest {
+            file_path: path.to_string(),
+            group: "crashes".to_string(),
+            issues: vec![IssueRef { number: issue, source: IssueSource::Filename }],
+            commit_sha: sha.to_string(),
+            commit_date: "2024-10-14".to_string(),
+            origin_pr: Some(131000),
+            rollup_pr: None,
+            pr_format: Some(PrRecognizer::Bors),
+            kind,
+        }
+    }
+
+    /// Compare rendered output with `src/snapshots/<name>`
+    /// `UPDATE_SNAPSHOTS=1 cargo test` rewrites the snapshots after an intended change
+    /// (bump SCHEMA_VERSION if the JSON layout changed incompatibly).
+    pub(crate) fn assert_snapshot(name: &str, actual: &str) {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/snapshots")
+            .join(name);
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let expected = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            actual == expected,
+            "{} changed, rerun with UPDATE_SNAPSHOTS=1 to accept:\n{}",
+            name,
+            actual
+        );
+    }
+
+    /// Report covering every category, shared with the renderer tests
+    pub(crate) fn sample_report() -> AuditReport {
+        let timestamp = Utc.with_ymd_and_hms(2024, 11, 2, 12, 0, 0).unwrap();
+
+        let mut rollup = deleted("tests/crashes/124000.rs", 124000, "b".repeat(40).as_str(), DeletionKind::Deleted);
+        rollup.origin_pr = Some(131500);
+        rollup.rollup_pr = Some(131600);
+        rollup.pr_format = Some(PrRecognizer::RollupMerge);
+
+        let mut ui_group = deleted(
+            "tests/ui/crashes/ice-125000.rs",
+            125000,
+            "c".repeat(40).as_str(),
+            DeletionKind::Deleted,
+        );
+        ui_group.group = "clippy".to_string();
+
+        AuditReport::new(
+            RunMetadata {
+                tool_version: "0.1.0".to_string(),
+                generated_at: timestamp,
+                repository: GitHubRepo::default(),
+                head_sha: "f".repeat(40),
+                from: NaiveDate::from_ymd_opt(2024, 10, 1),
+                to: None,
+                issues_fetched_at: timestamp - chrono::Duration::hours(2),
+                cache_age_seconds: Some(7200),
+                cache_max_age_seconds: Some(86400),
+                cache_stale: false,
+            },
+            Findings {
+                out_of_sync: vec![
+                    IssueDeletions {
+                        issue_number: 124000,
+                        issue_open: true,
+                        title: Some("ICE: `|` in pattern position".to_string()),
+                        state_reason: None,
+                        files: vec![rollup],
+                        remaining_files: 0,
+                    },
+                    IssueDeletions {
+                        issue_number: 123456,
+                        issue_open: true,
+                        title: Some("ICE: unexpected type in trait solver".to_string()),
+                        state_reason: None,
+                        files: vec![deleted(
+                            "tests/crashes/123456.rs",
+                            123456,
+                            "a".repeat(40).as_str(),
+                            DeletionKind::Moved {
+                                new_path: "tests/ui/traits/issue-123456.rs".to_string(),
+                                similarity: 87,
+                            },
+                        )],
+                        remaining_files: 0,
+                    },
+                ],
+                partially_deleted: vec![IssueDeletions {
+                    issue_number: 125000,
+                    issue_open: true,
+                    title: None,
+                    state_reason: None,
+                    files: vec![ui_group],
+                    remaining_files: 1,
+                }],
+                synced: vec![IssueDeletions {
+                    issue_number: 120000,
+                    issue_open: false,
+                    title: Some("ICE <with> HTML & pipes | in title".to_string()),
+                    state_reason: Some(StateReason::Completed),
+                    files: vec![deleted("tests/crashes/120000-2.rs", 120000, "d".repeat(40).as_str(), DeletionKind::Deleted)],
+                    remaining_files: 0,
+                }],
+                closed_with_tests: Some(vec![ClosedWithTests {
+                    issue_number: 110000,
+                    files: vec!["110000.rs".to_string()],
+                    closed_at: Some(timestamp - chrono::Duration::days(30)),
+                    closing_pr: Some(129000),
+                }]),
+                issue_mismatches: vec![IssueMismatch {
+                    file_path: "tests/crashes/126000.rs".to_string(),
+                    issues: vec![
+                        IssueRef { number: 126000, source: IssueSource::Filename },
+                        IssueRef { number: 126001, source: IssueSource::Directive },
+                    ],
+                }],
+                pull_request_references: vec![PullRequestReference {
+                    pr_number: 127000,
+                    files: vec!["tests/crashes/127000.rs".to_string()],
+                }],
+                suppressed: vec![SuppressedFinding {
+                    category: FindingCategory::OutOfSync,
+                    issue_number: Some(100000),
+                    files: vec!["tests/crashes/100000.rs".to_string()],
+                    reason: "Tracking issue, kept open on purpose".to_string(),
+                    expires: NaiveDate::from_ymd_opt(2025, 6, 30),
+                }],
+            },
+            9000,
+        )
+    }
+
+    #[test]
+    fn test_json_snapshot() {
+        let report = sample_report();
+        let json = serde_json::to_string_pretty(&report).unwrap() + "\n";
+        assert_snapshot("audit_report.json", &json);
+
+        // The snapshot also reads back into the model
+        let parsed: AuditReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+    }
+
+    #[test]
+    fn test_statistics() {
+        let report = sample_report();
+        let stats = &report.statistics;
+
+        assert_eq!(
+            report.out_of_sync.iter().map(|i| i.issue_number).collect::<Vec<_>>(),
+            vec![123456, 124000]
+        );
+        assert_eq!(stats.files_deleted, 4);
+        assert_eq!(stats.files_with_open_issues, 3);
+        assert_eq!(stats.files_with_closed_issues, 1);
+        assert_eq!(stats.files_moved, 1);
+        assert_eq!(stats.files_deleted_outright, 3);
+        assert_eq!(stats.stale_test_files, Some(1));
+        assert_eq!(report.finding_count(FailOn::OutOfSync), 2);
+        assert_eq!(report.finding_count(FailOn::Partial), 1);
+        assert_eq!(report.finding_count(FailOn::Any), 6);
+        assert_eq!(
+            stats.groups.iter().map(|g| (g.name.as_str(), g.files_deleted)).collect::<Vec<_>>(),
+            vec![("crashes", 3), ("clippy", 1)]
+        );
+    }
+
+    #[test]
+    fn test_statistics_shared_file() {
+        // One file with a `known-bug` directive for an open and a closed issue
+        let file = |issue| deleted("tests/crashes/130000.rs", issue, "e".repeat(40).as_str(), DeletionKind::Deleted);
+        let findings = Findings {
+            out_of_sync: vec![IssueDeletions {
+                issue_number: 130000,
+                issue_open: true,
+                title: None,
+                state_reason: None,
+                files: vec![file(130000)],
+                remaining_files: 0,
+            }],
+            synced: vec![IssueDeletions {
+                issue_number: 130001,
+                issue_open: false,
+                title: None,
+                state_reason: None,
+                files: vec![file(130001)],
+                remaining_files: 0,
+            }],
+            ..Findings::default()
+        };
+        let report = AuditReport::new(sample_report().metadata, findings, 1);
+        let stats = &report.statistics;
+
+        assert_eq!(stats.files_deleted, 1);
+        assert_eq!(stats.files_with_open_issues, 1);
+        assert_eq!(stats.files_with_closed_issues, 0);
+        assert_eq!(stats.files_deleted_outright, 1);
+        assert_eq!(stats.groups[0].files_deleted, 1);
+        assert_eq!(stats.groups[0].issues_out_of_sync, 1);
+    }
+}
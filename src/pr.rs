@@ -0,0 +1,170 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// A commit message format that references a pull request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrRecognizer {
+    /// bors merge: "Auto merge of #N - user:branch, r=reviewer"
+    Bors,
+    /// GitHub merge commit: "Merge pull request #N from user/branch"
+    GithubMerge,
+    /// Rollup sub-merge: "Rollup merge of #N - user:branch, r=reviewer"
+    RollupMerge,
+    /// Squash or merge-queue title: "Fix the thing (#N)"
+    Squash,
+}
+
+/// A PR reference together with the recognizer that found it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrRef {
+    pub number: u64,
+    pub recognizer: PrRecognizer,
+}
+
+impl PrRecognizer {
+    /// Default recognizer order, most specific first
+    pub const ALL: [PrRecognizer; 4] = [
+        PrRecognizer::Bors,
+        PrRecognizer::GithubMerge,
+        PrRecognizer::RollupMerge,
+        PrRecognizer::Squash,
+    ];
+
+    /// Name of the recognizer as used on the command line
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Try to extract a PR number from a commit message in this format
+    pub fn recognize(self, message: &str) -> Option<u64> {
+        let first_line = message.lines().next().unwrap_or("").trim();
+
+        match self {
+            // bors puts this on the first line, but historically it has been
+            // matched anywhere in the message
+            PrRecognizer::Bors => {
+                let start = message.find("Auto merge of #")?;
+                leading_number(&message[start + "Auto merge of #".len()..])
+            }
+            PrRecognizer::GithubMerge => {
+                leading_number(first_line.strip_prefix("Merge pull request #")?)
+            }
+            PrRecognizer::RollupMerge => {
+                leading_number(first_line.strip_prefix("Rollup merge of #")?)
+            }
+            PrRecognizer::Squash => {
+                let rest = first_line.strip_suffix(')')?;
+                let start = rest.rfind("(#")?;
+                rest[start + 2..].parse().ok()
+            }
+        }
+    }
+}
+
+/// Extracts PR references from commit messages using an ordered list of recognizers
+#[derive(Debug, Clone)]
+pub struct PrExtractor {
+    recognizers: Vec<PrRecognizer>,
+}
+
+impl PrExtractor {
+    pub fn new(recognizers: Vec<PrRecognizer>) -> Self {
+        Self { recognizers }
+    }
+
+    /// Recognizers in the order they are tried
+    pub fn recognizers(&self) -> &[PrRecognizer] {
+        &self.recognizers
+    }
+
+    /// Return the PR reference from the first recognizer that matches
+    pub fn extract(&self, message: &str) -> Option<PrRef> {
+        self.recognizers.iter().find_map(|&recognizer| {
+            recognizer
+                .recognize(message)
+                .map(|number| PrRef { number, recognizer })
+        })
+    }
+}
+
+impl Default for PrExtractor {
+    fn default() -> Self {
+        Self::new(PrRecognizer::ALL.to_vec())
+    }
+}
+
+/// Parse the digits at the start of a string
+fn leading_number(s: &str) -> Option<u64> {
+    let number_str: String = s.chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+
+    number_str.parse::<u64>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_pr_number() {
+        let cases: &[(&str, Option<(u64, PrRecognizer)>)] = &[
+            (
+                "Auto merge of #147900 - Zalathar:rollup-ril6jsi, r=Zalathar",
+                Some((147900, PrRecognizer::Bors)),
+            ),
+            (
+                "Auto merge of #12345 - username:branch, r=reviewer",
+                Some((12345, PrRecognizer::Bors)),
+            ),
+            (
+                "Merge pull request #4321 from user/fix-ice\n\nFix ICE in foo",
+                Some((4321, PrRecognizer::GithubMerge)),
+            ),
+            (
+                "Rollup merge of #147826 - author:branch, r=reviewer\n\nFix ICE",
+                Some((147826, PrRecognizer::RollupMerge)),
+            ),
+            (
+                "Fix ICE when normalizing projections (#123456)\n\nLonger description",
+                Some((123456, PrRecognizer::Squash)),
+            ),
+            ("Regular commit message without PR", None),
+            ("Mention #12345 but not auto merge", None),
+            ("Fix (#abc)", None),
+        ];
+
+        let extractor = PrExtractor::default();
+        for (message, expected) in cases {
+            let actual = extractor
+                .extract(message)
+                .map(|pr| (pr.number, pr.recognizer));
+            assert_eq!(actual, *expected, "message: {:?}", message);
+        }
+    }
+
+    #[test]
+    fn test_extractor_respects_configured_recognizers() {
+        let bors_only = PrExtractor::new(vec![PrRecognizer::Bors]);
+        assert_eq!(bors_only.extract("Fix the thing (#123)"), None);
+        assert_eq!(
+            bors_only.extract("Auto merge of #1 - a:b, r=c").map(|pr| pr.number),
+            Some(1)
+        );
+
+        // Order decides which recognizer wins when several match
+        let message = "Merge pull request #5 from user/branch (#6)";
+        let squash_first = PrExtractor::new(vec![PrRecognizer::Squash, PrRecognizer::GithubMerge]);
+        assert_eq!(
+            squash_first.extract(message),
+            Some(PrRef { number: 6, recognizer: PrRecognizer::Squash })
+        );
+        assert_eq!(
+            PrExtractor::default().extract(message),
+            Some(PrRef { number: 5, recognizer: PrRecognizer::GithubMerge })
+        );
+    }
+}
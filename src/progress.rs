@@ -0,0 +1,92 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A progress message from the audit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMessage<'a> {
+    /// A line of output, possibly empty or ending in a blank line
+    Line(&'a str),
+    /// A running count that the next message replaces
+    Count(&'a str),
+}
+
+type Callback = Arc<dyn Fn(ProgressMessage<'_>) + Send + Sync>;
+
+/// Receiver for the audit's progress messages and warnings
+/// The default discards everything, so the library stays quiet unless a
+/// front-end asks for output; [`Progress::stderr`] prints like the command
+/// line tool does. Detail messages only arrive when `verbose` is set.
+#[derive(Clone, Default)]
+pub struct Progress {
+    callback: Option<Callback>,
+    verbose: bool,
+}
+
+impl Progress {
+    /// Send messages to `callback`
+    pub fn new(callback: impl Fn(ProgressMessage<'_>) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Some(Arc::new(callback)),
+            verbose: false,
+        }
+    }
+
+    /// Print messages to stderr, updating counts in place
+    pub fn stderr() -> Self {
+        // A count stays on its line until the next message overwrites it
+        let counting = AtomicBool::new(false);
+        Self::new(move |message| match message {
+            ProgressMessage::Line(line) => {
+                if counting.swap(false, Ordering::Relaxed) {
+                    eprint!("\r");
+                }
+                eprintln!("{}", line);
+            }
+            ProgressMessage::Count(count) => {
+                counting.store(true, Ordering::Relaxed);
+                eprint!("\r{}", count);
+            }
+        })
+    }
+
+    /// Also send detail messages
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        self.verbose
+    }
+
+    /// Report a step of the audit, its outcome or a warning
+    pub fn message(&self, message: impl fmt::Display) {
+        if let Some(callback) = &self.callback {
+            callback(ProgressMessage::Line(&message.to_string()));
+        }
+    }
+
+    /// Report a detail only shown in verbose mode
+    pub fn detail(&self, message: impl fmt::Display) {
+        if self.verbose {
+            self.message(message);
+        }
+    }
+
+    /// Report a running count, e.g. of scanned commits
+    pub fn count(&self, message: impl fmt::Display) {
+        if let Some(callback) = &self.callback {
+            callback(ProgressMessage::Count(&message.to_string()));
+        }
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("callback", &self.callback.is_some())
+            .field("verbose", &self.verbose)
+            .finish()
+    }
+}
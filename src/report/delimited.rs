@@ -0,0 +1,92 @@
+use crate::git::DeletionKind;
+use crate::issues::StateReason;
+use crate::model::{AuditReport, IssueDeletions};
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+/// One deleted crash test per issue, in the exported column order
+/// Columns are only ever appended so existing spreadsheets keep working.
+#[derive(Debug, Serialize)]
+struct Row<'a> {
+    file_path: &'a str,
+    issue_number: u64,
+    issue_state: &'static str,
+    commit_sha: &'a str,
+    commit_date: &'a str,
+    pr_number: Option<u64>,
+    category: &'static str,
+    rollup_pr: Option<u64>,
+    group: &'a str,
+    moved_to: Option<&'a str>,
+    issue_title: Option<&'a str>,
+    state_reason: Option<StateReason>,
+}
+
+/// Write one row per deleted file and issue, separated by `delimiter`
+pub fn write_delimited(report: &AuditReport, delimiter: u8) -> Result<String> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(Vec::new());
+
+    let categories: [(&[IssueDeletions], &str); 3] = [
+        (&report.out_of_sync, "out-of-sync"),
+        (&report.partially_deleted, "partial"),
+        (&report.synced, "synced"),
+    ];
+
+    for (issues, category) in categories {
+        for issue in issues {
+            for file in &issue.files {
+                writer
+                    .serialize(Row {
+                        file_path: &file.file_path,
+                        issue_number: issue.issue_number,
+                        issue_state: if issue.issue_open { "open" } else { "closed" },
+                        commit_sha: &file.commit_sha,
+                        commit_date: &file.commit_date,
+                        pr_number: file.responsible_pr(),
+                        category,
+                        rollup_pr: file.rollup_pr,
+                        group: &file.group,
+                        moved_to: match &file.kind {
+                            DeletionKind::Deleted => None,
+                            DeletionKind::Moved { new_path, .. } => Some(new_path),
+                        },
+                        issue_title: issue.title.as_deref(),
+                        state_reason: issue.state_reason,
+                    })
+                    .context("Failed to write CSV row")?;
+            }
+        }
+    }
+
+    let bytes = writer
+        .into_inner()
+        .context("Failed to finish CSV output")?;
+    String::from_utf8(bytes).context("CSV output is not valid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::{assert_snapshot, sample_report};
+
+    #[test]
+    fn test_csv_snapshot() {
+        assert_snapshot("audit_report.csv", &write_delimited(&sample_report(), b',').unwrap());
+    }
+
+    #[test]
+    fn test_csv_escaping() {
+        let mut report = sample_report();
+        report.out_of_sync[0].files[0].file_path = "tests/crashes/a,\"b\".rs".to_string();
+
+        let csv = write_delimited(&report, b',').unwrap();
+        let first_row = csv.lines().nth(1).unwrap();
+        assert!(first_row.starts_with("\"tests/crashes/a,\"\"b\"\".rs\",123456,open,"));
+
+        let tsv = write_delimited(&report, b'\t').unwrap();
+        assert!(tsv.starts_with("file_path\tissue_number\tissue_state\t"));
+        assert!(tsv.lines().nth(1).unwrap().starts_with("\"tests/crashes/a,\"\"b\"\".rs\"\t123456\topen\t"));
+    }
+}
@@ -0,0 +1,468 @@
+use super::{file_name, issue_prs, issue_summary, latest_date, percentage, stale_cache_warning};
+use crate::git::{DeletionKind, IssueSource};
+use crate::github::GitHubRepo;
+use crate::model::{AuditReport, IssueDeletions};
+use std::fmt::{self, Write};
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2em auto; max-width: 1200px; padding: 0 1em; color: #1f2328; }
+h1 { font-size: 1.6em; }
+h2 { font-size: 1.25em; margin-top: 2em; border-bottom: 1px solid #d0d7de; padding-bottom: .3em; }
+.meta { color: #59636e; }
+.warning { background: #fff8c5; border: 1px solid #d4a72c; padding: .5em .8em; }
+#filter { width: 100%; max-width: 30em; padding: .4em; font-size: 1em; margin: 1em 0; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 1em; }
+th, td { border: 1px solid #d0d7de; padding: .35em .6em; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+th[data-sort] { cursor: pointer; user-select: none; }
+th[data-dir="asc"]::after { content: " ▲"; }
+th[data-dir="desc"]::after { content: " ▼"; }
+td.number { text-align: right; }
+code { font-size: .9em; }
+.stats td:last-child { text-align: right; }
+"#;
+
+const SCRIPT: &str = r#"
+document.querySelectorAll("th[data-sort]").forEach(function (th) {
+  th.addEventListener("click", function () {
+    var table = th.closest("table");
+    var body = table.tBodies[0];
+    var index = th.cellIndex;
+    var numeric = th.dataset.sort === "number";
+    var dir = th.dataset.dir === "asc" ? "desc" : "asc";
+    table.querySelectorAll("th").forEach(function (h) { delete h.dataset.dir; });
+    th.dataset.dir = dir;
+    var key = function (row) {
+      var cell = row.cells[index];
+      return cell.dataset.value !== undefined ? cell.dataset.value : cell.textContent;
+    };
+    var rows = Array.prototype.slice.call(body.rows).sort(function (a, b) {
+      var x = key(a), y = key(b);
+      var c = numeric ? Number(x) - Number(y) : x.localeCompare(y);
+      return dir === "asc" ? c : -c;
+    });
+    rows.forEach(function (row) { body.appendChild(row); });
+  });
+});
+document.getElementById("filter").addEventListener("input", function (event) {
+  var query = event.target.value.toLowerCase();
+  document.querySelectorAll("table.filterable tbody tr").forEach(function (row) {
+    row.hidden = query !== "" && row.textContent.toLowerCase().indexOf(query) === -1;
+  });
+});
+"#;
+
+/// Write the report as a single self-contained HTML page
+pub fn write_html(out: &mut impl Write, report: &AuditReport) -> fmt::Result {
+    let metadata = &report.metadata;
+    let github_repo = &metadata.repository;
+    let title = format!("Crash test audit for {}", github_repo);
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html lang=\"en\">")?;
+    writeln!(out, "<head>")?;
+    writeln!(out, "<meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>{}</title>", escape(&title))?;
+    writeln!(out, "<style>{}</style>", STYLE)?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")?;
+    writeln!(out, "<h1>{}</h1>", escape(&title))?;
+    writeln!(
+        out,
+        "<p class=\"meta\">History scanned from {} to {} (up to <code>{}</code>). Open issues as of {}. Generated {} by rust-crash-audit {}.</p>",
+        metadata.from.map(|d| d.to_string()).unwrap_or_else(|| "the beginning".to_string()),
+        metadata.to.map(|d| d.to_string()).unwrap_or_else(|| "present".to_string()),
+        escape(&metadata.head_sha[..metadata.head_sha.len().min(8)]),
+        metadata.issues_fetched_at.format("%Y-%m-%d %H:%M UTC"),
+        metadata.generated_at.format("%Y-%m-%d %H:%M UTC"),
+        escape(&metadata.tool_version)
+    )?;
+    if let Some(warning) = stale_cache_warning(report) {
+        writeln!(out, "<p class=\"warning\">⚠️ {}</p>", escape(&warning))?;
+    }
+    writeln!(
+        out,
+        "<input id=\"filter\" type=\"search\" placeholder=\"Filter by issue, file, PR or date\">"
+    )?;
+
+    write_section(
+        out,
+        &format!("⚠️ Out of sync ({})", report.out_of_sync.len()),
+        "Issues still open although all their crash tests were deleted or moved.",
+    )?;
+    write_deletions_table(out, &report.out_of_sync, github_repo, false)?;
+
+    write_section(
+        out,
+        &format!("ℹ️ Partial cleanup ({})", report.partially_deleted.len()),
+        "Issues with some crash tests deleted and others remaining.",
+    )?;
+    write_deletions_table(out, &report.partially_deleted, github_repo, true)?;
+
+    write_section(
+        out,
+        &format!("✅ Synced ({})", report.synced.len()),
+        "Closed issues whose crash tests were all deleted.",
+    )?;
+    write_deletions_table(out, &report.synced, github_repo, false)?;
+
+    if let Some(closed_with_tests) = report.closed_with_tests.as_ref().filter(|c| !c.is_empty()) {
+        write_section(
+            out,
+            &format!("🧹 Stale crash tests ({})", closed_with_tests.len()),
+            "Closed issues whose crash tests are still present.",
+        )?;
+        writeln!(out, "<table class=\"filterable\">")?;
+        writeln!(
+            out,
+            "<thead><tr><th data-sort=\"number\">Issue</th><th data-sort=\"text\">Closed</th><th data-sort=\"number\">Closing PR</th><th data-sort=\"text\">Files still present</th></tr></thead>"
+        )?;
+        writeln!(out, "<tbody>")?;
+        for closed in closed_with_tests {
+            let closed_date = closed
+                .closed_at
+                .map(|d| d.date_naive().to_string())
+                .unwrap_or_default();
+            let closing_pr = closed
+                .closing_pr
+                .map(|pr| link(&github_repo.pull_url(pr), &format!("#{}", pr)))
+                .unwrap_or_default();
+            let files: Vec<String> = closed
+                .files
+                .iter()
+                .map(|f| format!("<code>{}</code>", escape(f)))
+                .collect();
+            writeln!(
+                out,
+                "<tr><td data-value=\"{}\">{}</td><td>{}</td><td data-value=\"{}\">{}</td><td>{}</td></tr>",
+                closed.issue_number,
+                link(&github_repo.issue_url(closed.issue_number), &format!("#{}", closed.issue_number)),
+                closed_date,
+                closed.closing_pr.map(|pr| pr.to_string()).unwrap_or_default(),
+                closing_pr,
+                files.join("<br>")
+            )?;
+        }
+        writeln!(out, "</tbody>")?;
+        writeln!(out, "</table>")?;
+    }
+
+    if !report.issue_mismatches.is_empty() {
+        write_section(
+            out,
+            &format!("❓ Issue mismatches ({})", report.issue_mismatches.len()),
+            "Files whose filename and known-bug directive disagree.",
+        )?;
+        writeln!(out, "<table class=\"filterable\">")?;
+        writeln!(
+            out,
+            "<thead><tr><th data-sort=\"text\">File</th><th>Filename</th><th>known-bug</th></tr></thead>"
+        )?;
+        writeln!(out, "<tbody>")?;
+        for mismatch in &report.issue_mismatches {
+            let by_source = |source: IssueSource| {
+                mismatch
+                    .issues
+                    .iter()
+                    .filter(|i| i.source == source)
+                    .map(|i| link(&github_repo.issue_url(i.number), &format!("#{}", i.number)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            writeln!(
+                out,
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+                escape(&mismatch.file_path),
+                by_source(IssueSource::Filename),
+                by_source(IssueSource::Directive)
+            )?;
+        }
+        writeln!(out, "</tbody>")?;
+        writeln!(out, "</table>")?;
+    }
+
+    if !report.pull_request_references.is_empty() {
+        write_section(
+            out,
+            &format!("🔀 Pull request references ({})", report.pull_request_references.len()),
+            "Crash tests whose number is a pull request, not an issue.",
+        )?;
+        writeln!(out, "<table class=\"filterable\">")?;
+        writeln!(
+            out,
+            "<thead><tr><th data-sort=\"number\">Pull request</th><th>Files</th></tr></thead>"
+        )?;
+        writeln!(out, "<tbody>")?;
+        for reference in &report.pull_request_references {
+            let files: Vec<String> = reference
+                .files
+                .iter()
+                .map(|f| format!("<code>{}</code>", escape(f)))
+                .collect();
+            writeln!(
+                out,
+                "<tr><td data-value=\"{}\">{}</td><td>{}</td></tr>",
+                reference.pr_number,
+                link(&github_repo.pull_url(reference.pr_number), &format!("#{}", reference.pr_number)),
+                files.join("<br>")
+            )?;
+        }
+        writeln!(out, "</tbody>")?;
+        writeln!(out, "</table>")?;
+    }
+
+    if !report.suppressed.is_empty() {
+        write_section(
+            out,
+            &format!("🔕 Suppressed by baseline ({})", report.suppressed.len()),
+            "Known findings listed in the baseline file.",
+        )?;
+        writeln!(out, "<table class=\"filterable\">")?;
+        writeln!(
+            out,
+            "<thead><tr><th data-sort=\"text\">Finding</th><th data-sort=\"text\">Category</th><th data-sort=\"text\">Reason</th><th data-sort=\"text\">Until</th></tr></thead>"
+        )?;
+        writeln!(out, "<tbody>")?;
+        for suppressed in &report.suppressed {
+            let subject = match suppressed.issue_number {
+                Some(issue_number) => link(&github_repo.issue_url(issue_number), &format!("#{}", issue_number)),
+                None => suppressed
+                    .files
+                    .iter()
+                    .map(|f| format!("<code>{}</code>", escape(f)))
+                    .collect::<Vec<_>>()
+                    .join("<br>"),
+            };
+            writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                subject,
+                escape(suppressed.category.name()),
+                escape(&suppressed.reason),
+                suppressed.expires.map(|d| d.to_string()).unwrap_or_default()
+            )?;
+        }
+        writeln!(out, "</tbody>")?;
+        writeln!(out, "</table>")?;
+    }
+
+    write_statistics(out, report)?;
+
+    writeln!(out, "<script>{}</script>", SCRIPT)?;
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")
+}
+
+/// Write a section heading with a short description
+fn write_section(out: &mut impl Write, heading: &str, description: &str) -> fmt::Result {
+    writeln!(out, "<h2>{}</h2>", escape(heading))?;
+    writeln!(out, "<p>{}</p>", escape(description))
+}
+
+/// Write a sortable table with one row per issue
+fn write_deletions_table(
+    out: &mut impl Write,
+    issues: &[IssueDeletions],
+    github_repo: &GitHubRepo,
+    show_remaining: bool,
+) -> fmt::Result {
+    if issues.is_empty() {
+        return writeln!(out, "<p><em>None</em></p>");
+    }
+
+    writeln!(out, "<table class=\"filterable\">")?;
+    write!(
+        out,
+        "<thead><tr><th data-sort=\"number\">Issue</th><th data-sort=\"text\">Deleted files</th>"
+    )?;
+    if show_remaining {
+        write!(out, "<th data-sort=\"number\">Remaining</th>")?;
+    }
+    writeln!(
+        out,
+        "<th data-sort=\"number\">PR</th><th data-sort=\"text\">Date</th></tr></thead>"
+    )?;
+    writeln!(out, "<tbody>")?;
+
+    for issue in issues {
+        let files: Vec<String> = issue
+            .files
+            .iter()
+            .map(|file| match &file.kind {
+                DeletionKind::Deleted => format!("<code>{}</code>", escape(&file_name(&file.file_path))),
+                DeletionKind::Moved { new_path, similarity } => format!(
+                    "<code>{}</code> → <code>{}</code> ({}%)",
+                    escape(&file_name(&file.file_path)),
+                    escape(new_path),
+                    similarity
+                ),
+            })
+            .collect();
+
+        let prs = issue_prs(issue);
+        let pr_links: Vec<String> = prs
+            .iter()
+            .map(|&(pr, rollup)| {
+                let pr_link = link(&github_repo.pull_url(pr), &format!("#{}", pr));
+                match rollup {
+                    Some(rollup) => format!(
+                        "{} via {}",
+                        pr_link,
+                        link(&github_repo.pull_url(rollup), &format!("#{}", rollup))
+                    ),
+                    None => pr_link,
+                }
+            })
+            .collect();
+        let pr_value = prs
+            .first()
+            .map(|(pr, _)| pr.to_string())
+            .unwrap_or_default();
+
+        let mut issue_cell = link(&github_repo.issue_url(issue.issue_number), &format!("#{}", issue.issue_number));
+        if let Some(summary) = issue_summary(issue) {
+            issue_cell.push_str(&format!("<br><small>{}</small>", escape(&summary)));
+        }
+
+        write!(
+            out,
+            "<tr><td data-value=\"{}\">{}</td><td>{}</td>",
+            issue.issue_number,
+            issue_cell,
+            files.join("<br>")
+        )?;
+        if show_remaining {
+            write!(out, "<td class=\"number\">{}</td>", issue.remaining_files)?;
+        }
+        writeln!(
+            out,
+            "<td data-value=\"{}\">{}</td><td>{}</td></tr>",
+            pr_value,
+            pr_links.join("<br>"),
+            escape(latest_date(issue))
+        )?;
+    }
+
+    writeln!(out, "</tbody>")?;
+    writeln!(out, "</table>")
+}
+
+/// Write the statistics block shown by the text report
+fn write_statistics(out: &mut impl Write, report: &AuditReport) -> fmt::Result {
+    let stats = &report.statistics;
+    let mut rows: Vec<(String, String)> = vec![
+        ("Crash test files deleted".to_string(), stats.files_deleted.to_string()),
+        (
+            "Files with open issues".to_string(),
+            format!(
+                "{} ({:.1}%)",
+                stats.files_with_open_issues,
+                percentage(stats.files_with_open_issues, stats.files_deleted)
+            ),
+        ),
+        (
+            "Files with closed issues".to_string(),
+            format!(
+                "{} ({:.1}%)",
+                stats.files_with_closed_issues,
+                percentage(stats.files_with_closed_issues, stats.files_deleted)
+            ),
+        ),
+        ("Files promoted to other tests".to_string(), stats.files_moved.to_string()),
+        ("Files deleted outright".to_string(), stats.files_deleted_outright.to_string()),
+    ];
+    if stats.groups.len() > 1 {
+        for group in &stats.groups {
+            rows.push((
+                format!("Group {}", group.name),
+                format!(
+                    "{} file(s) deleted, {} issue(s) out of sync, {} partial",
+                    group.files_deleted, group.issues_out_of_sync, group.issues_partially_deleted
+                ),
+            ));
+        }
+    }
+    rows.extend([
+        (
+            format!("Total open issues in {}", report.metadata.repository),
+            stats.total_open_issues.to_string(),
+        ),
+        ("Issues fully cleaned up".to_string(), stats.issues_synced.to_string()),
+        ("Issues needing attention".to_string(), stats.issues_out_of_sync.to_string()),
+        (
+            "Issues with partial cleanup".to_string(),
+            stats.issues_partially_deleted.to_string(),
+        ),
+        (
+            "Files with filename/directive mismatches".to_string(),
+            stats.issue_mismatches.to_string(),
+        ),
+    ]);
+    if stats.pull_request_references > 0 {
+        rows.push((
+            "Crash tests numbered after pull requests".to_string(),
+            stats.pull_request_references.to_string(),
+        ));
+    }
+    if stats.findings_suppressed > 0 {
+        rows.push((
+            "Findings suppressed by baseline".to_string(),
+            stats.findings_suppressed.to_string(),
+        ));
+    }
+    if let (Some(closed_issues), Some(stale_files)) = (stats.closed_issues_with_tests, stats.stale_test_files) {
+        rows.push((
+            "Closed issues with crash tests still present".to_string(),
+            format!("{} ({} files)", closed_issues, stale_files),
+        ));
+    }
+
+    writeln!(out, "<h2>Statistics</h2>")?;
+    writeln!(out, "<table class=\"stats\">")?;
+    writeln!(out, "<tbody>")?;
+    for (label, value) in rows {
+        writeln!(out, "<tr><td>{}</td><td>{}</td></tr>", escape(&label), escape(&value))?;
+    }
+    writeln!(out, "</tbody>")?;
+    writeln!(out, "</table>")
+}
+
+/// Link with escaped text
+fn link(url: &str, text: &str) -> String {
+    format!("<a href=\"{}\">{}</a>", escape(url), escape(text))
+}
+
+/// Escape text for use in HTML content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::{assert_snapshot, sample_report};
+
+    #[test]
+    fn test_html_snapshot() {
+        let mut out = String::new();
+        write_html(&mut out, &sample_report()).unwrap();
+        assert_snapshot("audit_report.html", &out);
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("<a href=\"x\">&'</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;");
+        assert_eq!(escape("tests/crashes/1.rs"), "tests/crashes/1.rs");
+    }
+}
@@ -0,0 +1,354 @@
+use super::{file_name, issue_prs, issue_summary, latest_date, percentage, stale_cache_warning, RenderOptions};
+use crate::git::{DeletedCrashTest, DeletionKind, IssueSource};
+use crate::github::GitHubRepo;
+use crate::model::{AuditReport, IssueDeletions};
+use std::fmt::{self, Write};
+
+/// File lists longer than this are collapsed into a `<details>` block
+const DETAILS_THRESHOLD: usize = 3;
+
+/// Write the report as GitHub-flavored Markdown
+pub fn write_markdown(out: &mut impl Write, report: &AuditReport, options: RenderOptions) -> fmt::Result {
+    let metadata = &report.metadata;
+    let github_repo = &metadata.repository;
+    let stats = &report.statistics;
+
+    writeln!(out, "## Crash test audit for {}", github_repo)?;
+    writeln!(out)?;
+    let from = metadata
+        .from
+        .map(|d| d.to_string())
+        .unwrap_or_else(|| "the beginning".to_string());
+    let to = metadata
+        .to
+        .map(|d| d.to_string())
+        .unwrap_or_else(|| "present".to_string());
+    writeln!(
+        out,
+        "History scanned from {} to {} (up to `{}`). Open issues as of {}.",
+        from,
+        to,
+        &metadata.head_sha[..metadata.head_sha.len().min(8)],
+        metadata.issues_fetched_at.format("%Y-%m-%d %H:%M UTC")
+    )?;
+    writeln!(out)?;
+    if let Some(warning) = stale_cache_warning(report) {
+        writeln!(out, "> ⚠️ {}", warning)?;
+        writeln!(out)?;
+    }
+
+    writeln!(out, "### ⚠️ Out of sync ({})", report.out_of_sync.len())?;
+    writeln!(out)?;
+    if report.out_of_sync.is_empty() {
+        writeln!(out, "✅ All fully deleted crash tests have properly closed issues!")?;
+        writeln!(out)?;
+    } else {
+        writeln!(out, "Issues still open although all their crash tests were deleted or moved.")?;
+        writeln!(out)?;
+        write_deletions_table(out, &report.out_of_sync, github_repo, false)?;
+    }
+
+    if !report.partially_deleted.is_empty() {
+        writeln!(out, "### ℹ️ Partial cleanup ({})", report.partially_deleted.len())?;
+        writeln!(out)?;
+        write_deletions_table(out, &report.partially_deleted, github_repo, true)?;
+    }
+
+    if options.include_synced && !report.synced.is_empty() {
+        writeln!(out, "### ✅ Synced ({})", report.synced.len())?;
+        writeln!(out)?;
+        write_deletions_table(out, &report.synced, github_repo, false)?;
+    }
+
+    if let Some(closed_with_tests) = report.closed_with_tests.as_ref().filter(|c| !c.is_empty()) {
+        writeln!(out, "### 🧹 Stale crash tests ({})", closed_with_tests.len())?;
+        writeln!(out)?;
+        writeln!(out, "| Issue | Closed | Closing PR | Files still present |")?;
+        writeln!(out, "|-------|--------|------------|---------------------|")?;
+        for closed in closed_with_tests {
+            let closed_date = closed
+                .closed_at
+                .map(|d| d.date_naive().to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            let closing_pr = closed
+                .closing_pr
+                .map(|pr| pr_link(github_repo, pr))
+                .unwrap_or_default();
+            let files: Vec<String> = closed.files.iter().map(|f| code(f)).collect();
+            writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                issue_link(github_repo, closed.issue_number),
+                closed_date,
+                closing_pr,
+                collapsible(&files)
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    if !report.issue_mismatches.is_empty() {
+        writeln!(out, "### ❓ Issue mismatches ({})", report.issue_mismatches.len())?;
+        writeln!(out)?;
+        writeln!(out, "| File | Filename | known-bug |")?;
+        writeln!(out, "|------|----------|-----------|")?;
+        for mismatch in &report.issue_mismatches {
+            let by_source = |source: IssueSource| {
+                mismatch
+                    .issues
+                    .iter()
+                    .filter(|i| i.source == source)
+                    .map(|i| issue_link(github_repo, i.number))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            writeln!(
+                out,
+                "| {} | {} | {} |",
+                code(&mismatch.file_path),
+                by_source(IssueSource::Filename),
+                by_source(IssueSource::Directive)
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    if !report.pull_request_references.is_empty() {
+        writeln!(out, "### 🔀 Pull request references ({})", report.pull_request_references.len())?;
+        writeln!(out)?;
+        writeln!(out, "| Pull request (not an issue) | Crash tests |")?;
+        writeln!(out, "|-----------------------------|-------------|")?;
+        for reference in &report.pull_request_references {
+            let files: Vec<String> = reference.files.iter().map(|f| code(f)).collect();
+            writeln!(
+                out,
+                "| {} | {} |",
+                pr_link(github_repo, reference.pr_number),
+                collapsible(&files)
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    if !report.suppressed.is_empty() {
+        writeln!(out, "### 🔕 Suppressed by baseline ({})", report.suppressed.len())?;
+        writeln!(out)?;
+        writeln!(out, "| Finding | Category | Reason | Until |")?;
+        writeln!(out, "|---------|----------|--------|-------|")?;
+        for suppressed in &report.suppressed {
+            let subject = match suppressed.issue_number {
+                Some(issue_number) => issue_link(github_repo, issue_number),
+                None => suppressed.files.iter().map(|f| code(f)).collect::<Vec<_>>().join("<br>"),
+            };
+            writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                subject,
+                suppressed.category.name(),
+                cell(&suppressed.reason),
+                suppressed.expires.map(|d| d.to_string()).unwrap_or_default()
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    writeln!(out, "### Statistics")?;
+    writeln!(out)?;
+    writeln!(out, "| | Count |")?;
+    writeln!(out, "|-|------:|")?;
+    writeln!(out, "| Crash test files deleted | {} |", stats.files_deleted)?;
+    writeln!(
+        out,
+        "| Files with open issues | {} ({:.1}%) |",
+        stats.files_with_open_issues,
+        percentage(stats.files_with_open_issues, stats.files_deleted)
+    )?;
+    writeln!(
+        out,
+        "| Files with closed issues | {} ({:.1}%) |",
+        stats.files_with_closed_issues,
+        percentage(stats.files_with_closed_issues, stats.files_deleted)
+    )?;
+    writeln!(out, "| Files promoted to other tests | {} |", stats.files_moved)?;
+    writeln!(out, "| Files deleted outright | {} |", stats.files_deleted_outright)?;
+    if stats.groups.len() > 1 {
+        for group in &stats.groups {
+            writeln!(
+                out,
+                "| Files deleted in {} | {} |",
+                cell(&group.name),
+                group.files_deleted
+            )?;
+        }
+    }
+    writeln!(out, "| Open issues in {} | {} |", github_repo, stats.total_open_issues)?;
+    writeln!(out, "| Issues fully cleaned up | {} |", stats.issues_synced)?;
+    writeln!(out, "| Issues needing attention | {} |", stats.issues_out_of_sync)?;
+    writeln!(out, "| Issues with partial cleanup | {} |", stats.issues_partially_deleted)?;
+    writeln!(out, "| Files with filename/directive mismatches | {} |", stats.issue_mismatches)?;
+    if stats.pull_request_references > 0 {
+        writeln!(out, "| Crash tests numbered after pull requests | {} |", stats.pull_request_references)?;
+    }
+    if stats.findings_suppressed > 0 {
+        writeln!(out, "| Findings suppressed by baseline | {} |", stats.findings_suppressed)?;
+    }
+    if let (Some(closed_issues), Some(stale_files)) = (stats.closed_issues_with_tests, stats.stale_test_files) {
+        writeln!(
+            out,
+            "| Closed issues with crash tests still present | {} ({} files) |",
+            closed_issues,
+            stale_files
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Write one table row per issue with its deleted files, PRs and latest date
+fn write_deletions_table(
+    out: &mut impl Write,
+    issues: &[IssueDeletions],
+    github_repo: &GitHubRepo,
+    show_remaining: bool,
+) -> fmt::Result {
+    if show_remaining {
+        writeln!(out, "| Issue | Deleted files | Remaining | PR | Date |")?;
+        writeln!(out, "|-------|---------------|----------:|----|------|")?;
+    } else {
+        writeln!(out, "| Issue | Deleted files | PR | Date |")?;
+        writeln!(out, "|-------|---------------|----|------|")?;
+    }
+
+    for issue in issues {
+        let files: Vec<String> = issue.files.iter().map(describe_file).collect();
+
+        let prs: Vec<String> = issue_prs(issue)
+            .into_iter()
+            .map(|(pr, rollup)| match rollup {
+                Some(rollup) => format!(
+                    "{} via {}",
+                    pr_link(github_repo, pr),
+                    pr_link(github_repo, rollup)
+                ),
+                None => pr_link(github_repo, pr),
+            })
+            .collect();
+        let date = latest_date(issue);
+        let issue_cell = match issue_summary(issue) {
+            Some(summary) => format!("{} {}", issue_link(github_repo, issue.issue_number), plain(&summary)),
+            None => issue_link(github_repo, issue.issue_number),
+        };
+
+        if show_remaining {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                issue_cell,
+                collapsible(&files),
+                issue.remaining_files,
+                prs.join("<br>"),
+                date
+            )?;
+        } else {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                issue_cell,
+                collapsible(&files),
+                prs.join("<br>"),
+                date
+            )?;
+        }
+    }
+
+    writeln!(out)
+}
+
+/// Describe a deleted file, including where it was moved to
+fn describe_file(file: &DeletedCrashTest) -> String {
+    match &file.kind {
+        DeletionKind::Deleted => code(&file_name(&file.file_path)),
+        DeletionKind::Moved { new_path, similarity } => format!(
+            "{} → {} ({}%)",
+            code(&file_name(&file.file_path)),
+            code(new_path),
+            similarity
+        ),
+    }
+}
+
+/// Join table cell entries, collapsing long lists into a `<details>` block
+fn collapsible(entries: &[String]) -> String {
+    if entries.len() <= DETAILS_THRESHOLD {
+        entries.join("<br>")
+    } else {
+        format!(
+            "<details><summary>{} files</summary>{}</details>",
+            entries.len(),
+            entries.join("<br>")
+        )
+    }
+}
+
+/// `owner/repo#N` shorthand linking to the issue
+fn issue_link(github_repo: &GitHubRepo, number: u64) -> String {
+    format!("[{}#{}]({})", github_repo, number, github_repo.issue_url(number))
+}
+
+/// `owner/repo#N` shorthand linking to the pull request
+fn pr_link(github_repo: &GitHubRepo, number: u64) -> String {
+    format!("[{}#{}]({})", github_repo, number, github_repo.pull_url(number))
+}
+
+/// Inline code span that is safe inside a table cell
+fn code(text: &str) -> String {
+    format!("`{}`", cell(text))
+}
+
+/// Escape characters that would break a table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Free text such as issue titles, which may contain HTML-like `<...>`
+fn plain(text: &str) -> String {
+    cell(text).replace('<', "&lt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::{assert_snapshot, sample_report};
+
+    #[test]
+    fn test_markdown_snapshot() {
+        let mut out = String::new();
+        write_markdown(&mut out, &sample_report(), RenderOptions { include_synced: true }).unwrap();
+        assert_snapshot("audit_report.md", &out);
+    }
+
+    #[test]
+    fn test_stale_cache_warning() {
+        let mut report = sample_report();
+        let options = RenderOptions::default();
+        let mut out = String::new();
+        write_markdown(&mut out, &report, options).unwrap();
+        assert!(!out.contains("out of date"));
+
+        report.metadata.cache_stale = true;
+        report.metadata.cache_age_seconds = Some(3 * 86400);
+        let mut out = String::new();
+        write_markdown(&mut out, &report, options).unwrap();
+        assert!(out.contains(
+            "> ⚠️ Issue states come from a cache last updated 3 days ago, older than the maximum of 1 day"
+        ));
+    }
+
+    #[test]
+    fn test_collapsible() {
+        let files: Vec<String> = (1..=4).map(|n| format!("`{}.rs`", n)).collect();
+        assert_eq!(collapsible(&files[..2]), "`1.rs`<br>`2.rs`");
+        assert!(collapsible(&files).starts_with("<details><summary>4 files</summary>"));
+        assert_eq!(code("a|b.rs"), "`a\\|b.rs`");
+    }
+}
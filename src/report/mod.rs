@@ -0,0 +1,131 @@
+mod delimited;
+mod html;
+mod markdown;
+mod text;
+
+use crate::cache;
+use crate::model::{AuditReport, IssueDeletions};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::path::Path;
+use std::time::Duration;
+
+/// Output format for the audit report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable report
+    Text,
+    /// Machine-readable `AuditReport`, see the README for the schema
+    Json,
+    /// Tables for pasting into a GitHub issue or Zulip
+    Markdown,
+    /// Single self-contained page with sortable, filterable tables
+    Html,
+    /// One row per deleted file, comma-separated
+    Csv,
+    /// One row per deleted file, tab-separated
+    Tsv,
+}
+
+/// Settings shared by the renderers
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions {
+    /// Also list issues that are closed with all their tests deleted
+    pub include_synced: bool,
+}
+
+/// Render the report in the given format
+pub fn render(report: &AuditReport, format: OutputFormat, options: RenderOptions) -> Result<String> {
+    let mut out = String::new();
+    match format {
+        OutputFormat::Text => text::write_text(&mut out, report)
+            .context("Failed to render text report")?,
+        OutputFormat::Json => {
+            out = serde_json::to_string_pretty(report)
+                .context("Failed to serialize report")?;
+            out.push('\n');
+        }
+        OutputFormat::Markdown => markdown::write_markdown(&mut out, report, options)
+            .context("Failed to render Markdown report")?,
+        OutputFormat::Html => html::write_html(&mut out, report)
+            .context("Failed to render HTML report")?,
+        OutputFormat::Csv => out = delimited::write_delimited(report, b',')?,
+        OutputFormat::Tsv => out = delimited::write_delimited(report, b'\t')?,
+    }
+    Ok(out)
+}
+
+/// Warning for reports built from an issue cache that was older than the
+/// maximum age and could not be refreshed
+fn stale_cache_warning(report: &AuditReport) -> Option<String> {
+    let metadata = &report.metadata;
+    if !metadata.cache_stale {
+        return None;
+    }
+
+    let describe = |secs: Option<u64>| {
+        secs.map(|secs| cache::format_duration(Duration::from_secs(secs)))
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+    Some(format!(
+        "Issue states come from a cache last updated {} ago, older than the maximum of {}; results may be out of date",
+        describe(metadata.cache_age_seconds),
+        describe(metadata.cache_max_age_seconds)
+    ))
+}
+
+/// Unique PRs that deleted an issue's tests, as (PR, rollup it landed through)
+fn issue_prs(issue: &IssueDeletions) -> Vec<(u64, Option<u64>)> {
+    let mut prs = Vec::new();
+    for file in &issue.files {
+        let pr = match (file.origin_pr, file.rollup_pr) {
+            (Some(origin), Some(rollup)) => Some((origin, Some(rollup))),
+            _ => file.responsible_pr().map(|pr| (pr, None)),
+        };
+        if let Some(pr) = pr {
+            if !prs.contains(&pr) {
+                prs.push(pr);
+            }
+        }
+    }
+    prs
+}
+
+/// Title and close reason of an issue, as far as they are known,
+/// e.g. "ICE: unreachable code (closed as not planned)"
+fn issue_summary(issue: &IssueDeletions) -> Option<String> {
+    let reason = issue.state_reason.filter(|_| !issue.issue_open);
+    match (&issue.title, reason) {
+        (Some(title), Some(reason)) => Some(format!("{} (closed as {})", title, reason)),
+        (Some(title), None) => Some(title.clone()),
+        (None, Some(reason)) => Some(format!("closed as {}", reason)),
+        (None, None) => None,
+    }
+}
+
+/// Date of the most recent deletion for an issue
+fn latest_date(issue: &IssueDeletions) -> &str {
+    issue
+        .files
+        .iter()
+        .map(|f| f.commit_date.as_str())
+        .max()
+        .unwrap_or_default()
+}
+
+/// Get the filename component of a path, falling back to the full path
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
+
+fn percentage(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        (count as f64 / total as f64) * 100.0
+    }
+}
@@ -0,0 +1,366 @@
+use super::{file_name, issue_summary, percentage, stale_cache_warning};
+use crate::git::{DeletionKind, IssueSource};
+use crate::github::GitHubRepo;
+use crate::model::{AuditReport, IssueDeletions};
+use std::fmt::{self, Write};
+
+/// Write the human-readable report
+pub fn write_text(out: &mut impl Write, report: &AuditReport) -> fmt::Result {
+    let github_repo = &report.metadata.repository;
+    let stats = &report.statistics;
+
+    if let Some(warning) = stale_cache_warning(report) {
+        writeln!(out, "⚠️  {}", warning)?;
+        writeln!(out)?;
+    }
+
+    // Section 1: Out-of-sync issues (fully deleted but still open)
+    // Split by how the tests left tests/crashes/: a test promoted to a ui test
+    // usually means the issue was fixed, an outright deletion may be a mistake
+    let (promoted, deleted_outright): (Vec<_>, Vec<_>) = report
+        .out_of_sync
+        .iter()
+        .partition(|issue| issue.files.iter().all(|f| f.is_moved()));
+
+    if !promoted.is_empty() {
+        writeln!(out, "⚠️  Out-of-sync issues (ALL files promoted to other tests but issue still open):")?;
+        writeln!(out)?;
+        for issue in promoted {
+            write_out_of_sync_issue(out, issue, github_repo)?;
+        }
+    }
+
+    if !deleted_outright.is_empty() {
+        writeln!(out, "⚠️  Out-of-sync issues (ALL files deleted outright but issue still open):")?;
+        writeln!(out)?;
+        for issue in deleted_outright {
+            write_out_of_sync_issue(out, issue, github_repo)?;
+        }
+    }
+
+    // Section 2: Partially deleted issues
+    if !report.partially_deleted.is_empty() {
+        writeln!(out, "ℹ️  Partial cleanup (some files deleted, others remain):")?;
+        writeln!(out)?;
+        for issue in &report.partially_deleted {
+            let deleted_files: Vec<String> = issue
+                .files
+                .iter()
+                .map(|f| file_name(&f.file_path))
+                .collect();
+
+            writeln!(
+                out,
+                "  • Issue #{}: {} file(s) deleted, {} remain",
+                issue.issue_number,
+                issue.files.len(),
+                issue.remaining_files
+            )?;
+            if let Some(summary) = issue_summary(issue) {
+                writeln!(out, "    {}", summary)?;
+            }
+            writeln!(out, "    Deleted: {}", deleted_files.join(", "))?;
+            writeln!(out, "    Issue: {}", github_repo.issue_url(issue.issue_number))?;
+            writeln!(out)?;
+        }
+    }
+
+    // Section 3: Closed issues whose crash tests are still present
+    if let Some(closed_with_tests) = report.closed_with_tests.as_ref().filter(|c| !c.is_empty()) {
+        writeln!(out, "🧹 Stale crash tests (issue already closed but tests still present):")?;
+        writeln!(out)?;
+        for closed in closed_with_tests {
+            let closed_date = closed
+                .closed_at
+                .map(|d| d.date_naive().to_string())
+                .unwrap_or_else(|| "unknown date".to_string());
+
+            if let Some(pr_number) = closed.closing_pr {
+                writeln!(
+                    out,
+                    "  • Issue #{}: closed {} by PR #{}, {} file(s) remain ({})",
+                    closed.issue_number,
+                    closed_date,
+                    pr_number,
+                    closed.files.len(),
+                    closed.files.join(", ")
+                )?;
+            } else {
+                writeln!(
+                    out,
+                    "  • Issue #{}: closed {}, {} file(s) remain ({})",
+                    closed.issue_number,
+                    closed_date,
+                    closed.files.len(),
+                    closed.files.join(", ")
+                )?;
+            }
+            writeln!(out, "    Issue: {}", github_repo.issue_url(closed.issue_number))?;
+            if let Some(pr_number) = closed.closing_pr {
+                writeln!(out, "    PR: {}", github_repo.pull_url(pr_number))?;
+            }
+            writeln!(out)?;
+        }
+    }
+
+    // Section 4: Filename / known-bug directive mismatches
+    if !report.issue_mismatches.is_empty() {
+        writeln!(out, "❓ Issue mismatches (filename and known-bug directive disagree):")?;
+        writeln!(out)?;
+        for mismatch in &report.issue_mismatches {
+            let by_source = |source: IssueSource| {
+                mismatch
+                    .issues
+                    .iter()
+                    .filter(|i| i.source == source)
+                    .map(|i| format!("#{}", i.number))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            writeln!(out, "  • {}", mismatch.file_path)?;
+            writeln!(
+                out,
+                "    Filename: {}, known-bug: {}",
+                by_source(IssueSource::Filename),
+                by_source(IssueSource::Directive)
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    // Section 5: Crash tests numbered after a pull request
+    if !report.pull_request_references.is_empty() {
+        writeln!(out, "🔀 Pull request references (crash test number is a pull request, not an issue):")?;
+        writeln!(out)?;
+        for reference in &report.pull_request_references {
+            writeln!(out, "  • PR #{}: {}", reference.pr_number, reference.files.join(", "))?;
+            writeln!(out, "    PR: {}", github_repo.pull_url(reference.pr_number))?;
+        }
+        writeln!(out)?;
+    }
+
+    // Section 6: Findings hidden by the baseline file
+    if !report.suppressed.is_empty() {
+        writeln!(out, "🔕 Suppressed by baseline:")?;
+        writeln!(out)?;
+        for suppressed in &report.suppressed {
+            let subject = match suppressed.issue_number {
+                Some(issue_number) => format!("Issue #{}", issue_number),
+                None => suppressed.files.join(", "),
+            };
+            let expires = suppressed
+                .expires
+                .map(|d| format!(" (until {})", d))
+                .unwrap_or_default();
+            writeln!(
+                out,
+                "  • {} ({}): {}{}",
+                subject,
+                suppressed.category.name(),
+                suppressed.reason,
+                expires
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    // Section 7: Statistics
+    writeln!(out, "─────────────────────────────────────────────────")?;
+    writeln!(out, "Statistics:")?;
+    writeln!(out, "  Total crash test files deleted: {}", stats.files_deleted)?;
+    writeln!(
+        out,
+        "  Files with open issues: {} ({:.1}%)",
+        stats.files_with_open_issues,
+        percentage(stats.files_with_open_issues, stats.files_deleted)
+    )?;
+    writeln!(
+        out,
+        "  Files with closed issues: {} ({:.1}%)",
+        stats.files_with_closed_issues,
+        percentage(stats.files_with_closed_issues, stats.files_deleted)
+    )?;
+    writeln!(
+        out,
+        "  Files promoted to other tests: {}",
+        stats.files_moved
+    )?;
+    writeln!(
+        out,
+        "  Files deleted outright: {}",
+        stats.files_deleted_outright
+    )?;
+    // Per-group counts only matter with more than one watch group
+    if stats.groups.len() > 1 {
+        writeln!(out, "  By group:")?;
+        for group in &stats.groups {
+            writeln!(
+                out,
+                "    {}: {} file(s) deleted, {} issue(s) out of sync, {} partial",
+                group.name, group.files_deleted, group.issues_out_of_sync, group.issues_partially_deleted
+            )?;
+        }
+    }
+    writeln!(out)?;
+    writeln!(out, "  Total open issues in {}: {}", github_repo, stats.total_open_issues)?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "  Issues fully cleaned up: {}",
+        stats.issues_synced
+    )?;
+    writeln!(
+        out,
+        "  Issues needing attention: {}",
+        stats.issues_out_of_sync
+    )?;
+    if stats.issues_partially_deleted > 0 {
+        writeln!(
+            out,
+            "  Issues with partial cleanup: {}",
+            stats.issues_partially_deleted
+        )?;
+    }
+    if stats.issue_mismatches > 0 {
+        writeln!(
+            out,
+            "  Files with filename/directive mismatches: {}",
+            stats.issue_mismatches
+        )?;
+    }
+    if stats.pull_request_references > 0 {
+        writeln!(
+            out,
+            "  Crash tests numbered after pull requests: {}",
+            stats.pull_request_references
+        )?;
+    }
+    if stats.findings_suppressed > 0 {
+        writeln!(
+            out,
+            "  Findings suppressed by baseline: {}",
+            stats.findings_suppressed
+        )?;
+    }
+    if let (Some(closed_issues), Some(stale_files)) = (stats.closed_issues_with_tests, stats.stale_test_files) {
+        writeln!(
+            out,
+            "  Closed issues with crash tests still present: {} ({} files)",
+            closed_issues,
+            stale_files
+        )?;
+    }
+    writeln!(out, "─────────────────────────────────────────────────")?;
+
+    // Final message
+    if report.out_of_sync.is_empty() {
+        writeln!(out, "\n✅ All fully deleted crash tests have properly closed issues!")?;
+    } else {
+        writeln!(
+            out,
+            "\n⚠️  Found {} issue(s) that need attention.",
+            report.out_of_sync.len()
+        )?;
+        writeln!(out, "\nThese issues should either:")?;
+        writeln!(out, "  1. Be closed (if the issue is actually fixed)")?;
+        writeln!(out, "  2. Have tests restored (if removed by mistake)")?;
+    }
+
+    Ok(())
+}
+
+/// Write a single out-of-sync issue with its files and links
+fn write_out_of_sync_issue(out: &mut impl Write, issue: &IssueDeletions, github_repo: &GitHubRepo) -> fmt::Result {
+    let issue_number = issue.issue_number;
+    let files = &issue.files;
+
+    // Get unique PR numbers
+    let pr_numbers: Vec<u64> = files
+        .iter()
+        .filter_map(|f| f.responsible_pr())
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    // List deleted files
+    let deleted_files: Vec<String> = files
+        .iter()
+        .map(|f| file_name(&f.file_path))
+        .collect();
+
+    if files.len() == 1 {
+        let file = &files[0];
+        let action = match &file.kind {
+            DeletionKind::Deleted => "deleted".to_string(),
+            DeletionKind::Moved { new_path, similarity } => {
+                format!("moved to {} ({}% similar)", new_path, similarity)
+            }
+        };
+        if let (Some(origin_pr), Some(rollup_pr)) = (file.origin_pr, file.rollup_pr) {
+            writeln!(
+                out,
+                "  • Issue #{}: {} {} in PR #{} via rollup #{} (commit {}, {})",
+                issue_number,
+                file.file_path,
+                action,
+                origin_pr,
+                rollup_pr,
+                &file.commit_sha[..8],
+                file.commit_date
+            )?;
+        } else if let Some(pr_number) = file.responsible_pr() {
+            writeln!(
+                out,
+                "  • Issue #{}: {} {} in PR #{} (commit {}, {})",
+                issue_number,
+                file.file_path,
+                action,
+                pr_number,
+                &file.commit_sha[..8],
+                file.commit_date
+            )?;
+        } else {
+            writeln!(
+                out,
+                "  • Issue #{}: {} {} in commit {} ({})",
+                issue_number,
+                file.file_path,
+                action,
+                &file.commit_sha[..8],
+                file.commit_date
+            )?;
+        }
+    } else {
+        writeln!(
+            out,
+            "  • Issue #{}: {} files deleted ({})",
+            issue_number,
+            files.len(),
+            deleted_files.join(", ")
+        )?;
+        for file in files {
+            if let DeletionKind::Moved { new_path, similarity } = &file.kind {
+                writeln!(
+                    out,
+                    "    {} → {} ({}% similar)",
+                    file_name(&file.file_path),
+                    new_path,
+                    similarity
+                )?;
+            }
+        }
+    }
+
+    if let Some(summary) = issue_summary(issue) {
+        writeln!(out, "    {}", summary)?;
+    }
+    writeln!(out, "    Issue: {}", github_repo.issue_url(issue_number))?;
+    if !pr_numbers.is_empty() {
+        for pr_number in pr_numbers {
+            writeln!(out, "    PR: {}", github_repo.pull_url(pr_number))?;
+        }
+    }
+    writeln!(out)?;
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::{command, fixture};
+use std::process::Output;
+
+fn cache(fixture: &common::Fixture, args: &[&str]) -> Output {
+    command(fixture)
+        .arg("cache")
+        .args(args)
+        .args(["--owner", "example", "--repo", "project"])
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_cache_status_and_query() {
+    let fixture = fixture(&[100, 300]);
+
+    let status = stdout(&cache(&fixture, &["status"]));
+    assert!(status.contains("example/project\n"), "{}", status);
+    assert!(status.contains("Schema version: 3"), "{}", status);
+    assert!(status.contains("Open issues: 2"), "{}", status);
+
+    let query = stdout(&cache(&fixture, &["query", "100"]));
+    assert!(query.starts_with("#100 ICE\n  State: open\n"), "{}", query);
+    let query = stdout(&cache(&fixture, &["query", "200"]));
+    assert!(query.starts_with("#200 is not in the cache, so it counts as closed"), "{}", query);
+
+    // The repository comes from the origin remote, like for the audit
+    let inferred = command(&fixture)
+        .current_dir(fixture.repo.path())
+        .args(["cache", "query", "300"])
+        .output()
+        .unwrap();
+    let query = stdout(&inferred);
+    assert!(query.starts_with("#300 ICE\n  State: open\n"), "{}", query);
+}
+
+#[test]
+fn test_cache_export_import_clear() {
+    let fixture = fixture(&[100, 300]);
+    let exported = fixture.workdir.path().join("issues.json");
+    let exported = exported.to_str().unwrap();
+
+    stdout(&cache(&fixture, &["export", exported]));
+    stdout(&cache(&fixture, &["clear"]));
+    assert!(!fixture.cache_file().exists());
+    assert_eq!(cache(&fixture, &["query", "100"]).status.code(), Some(2));
+
+    // Not as the cache of another repository
+    let other = command(&fixture)
+        .args(["cache", "import", exported, "--owner", "example", "--repo", "other"])
+        .output()
+        .unwrap();
+    assert_eq!(other.status.code(), Some(2));
+
+    let imported = stdout(&cache(&fixture, &["import", exported]));
+    assert!(imported.starts_with("Imported 2 issues of example/project"), "{}", imported);
+    let query = stdout(&cache(&fixture, &["query", "300"]));
+    assert!(query.contains("State: open"), "{}", query);
+}
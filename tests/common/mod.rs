@@ -0,0 +1,120 @@
+// Each test binary uses a different subset of these helpers
+#![allow(dead_code)]
+
+use git2::{Commit, Repository};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// A fixture repository and a working directory with a pre-seeded issue cache
+/// (under `xdg-cache/` in the working directory)
+pub struct Fixture {
+    pub repo: TempDir,
+    pub workdir: TempDir,
+}
+
+fn commit(repo: &Repository, message: &str, changes: &[(&str, Option<&str>)]) {
+    let workdir = repo.workdir().unwrap().to_path_buf();
+    let mut index = repo.index().unwrap();
+    for (path, content) in changes {
+        let full_path = workdir.join(path);
+        match content {
+            Some(content) => {
+                std::fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+                std::fs::write(&full_path, content).unwrap();
+                index.add_path(Path::new(path)).unwrap();
+            }
+            None => {
+                std::fs::remove_file(&full_path).unwrap();
+                index.remove_path(Path::new(path)).unwrap();
+            }
+        }
+    }
+    index.write().unwrap();
+
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+    let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+    let parents: Vec<&Commit> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+        .unwrap();
+}
+
+/// Issue 100: all tests deleted (out of sync while open)
+/// Issue 200: all tests deleted (synced while closed)
+/// Issue 300: one of two tests deleted (partial cleanup)
+pub fn fixture(open_issues: &[u64]) -> Fixture {
+    let repo_dir = TempDir::new().unwrap();
+    let repo = Repository::init(repo_dir.path()).unwrap();
+    repo.remote("origin", "https://github.com/example/project.git").unwrap();
+
+    commit(
+        &repo,
+        "Add crash tests",
+        &[
+            ("tests/crashes/100.rs", Some("fn main() {}\n")),
+            ("tests/crashes/200.rs", Some("fn main() {}\n")),
+            ("tests/crashes/300.rs", Some("fn main() {}\n")),
+            ("tests/crashes/300-2.rs", Some("fn main() { 2; }\n")),
+        ],
+    );
+    commit(&repo, "Fix 100 (#1)", &[("tests/crashes/100.rs", None)]);
+    commit(&repo, "Fix 200 (#2)", &[("tests/crashes/200.rs", None)]);
+    commit(&repo, "Fix 300 (#3)", &[("tests/crashes/300.rs", None)]);
+
+    let workdir = TempDir::new().unwrap();
+    let fixture = Fixture { repo: repo_dir, workdir };
+    let cache_file = fixture.cache_file();
+    std::fs::create_dir_all(cache_file.parent().unwrap()).unwrap();
+    let issues: Vec<_> = open_issues
+        .iter()
+        .map(|number| serde_json::json!({ "number": number, "title": "ICE", "state": "open" }))
+        .collect();
+    let cache = serde_json::json!({
+        "schema_version": 3,
+        "repository": { "owner": "example", "name": "project" },
+        "timestamp": chrono::Utc::now(),
+        "issue_count": open_issues.len(),
+        "issues": issues,
+        "pull_request_numbers": [],
+    });
+    std::fs::write(cache_file, cache.to_string()).unwrap();
+
+    fixture
+}
+
+impl Fixture {
+    /// Stands in for the user's cache directory through `XDG_CACHE_HOME`
+    pub fn xdg_cache_home(&self) -> PathBuf {
+        self.workdir.path().join("xdg-cache")
+    }
+
+    pub fn cache_file(&self) -> PathBuf {
+        self.xdg_cache_home().join("rust-crash-audit/example/project/open_issues.json")
+    }
+}
+
+/// The binary, set up to run from the fixture's working directory
+pub fn command(fixture: &Fixture) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_rust-crash-audit"));
+    command
+        .current_dir(fixture.workdir.path())
+        .env("XDG_CACHE_HOME", fixture.xdg_cache_home())
+        .env_remove("GITHUB_TOKEN");
+    command
+}
+
+/// Run the binary from the fixture's working directory
+pub fn output(fixture: &Fixture, args: &[&str]) -> Output {
+    command(fixture)
+        .arg(fixture.repo.path())
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Run the binary from the fixture's working directory and return its exit code
+pub fn run(fixture: &Fixture, args: &[&str]) -> i32 {
+    output(fixture, args).status.code().unwrap()
+}
+
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{fixture, run};
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn test_exit_code_out_of_sync() {
+    let fixture = fixture(&[100, 300]);
+    assert_eq!(run(&fixture, &[]), 1);
+    assert_eq!(run(&fixture, &["--fail-on", "out-of-sync"]), 1);
+    assert_eq!(run(&fixture, &["--fail-threshold", "1"]), 0);
+}
+
+#[test]
+fn test_exit_code_clean() {
+    // Nothing open: 100 and 200 are synced, only the partial cleanup remains
+    let fixture = fixture(&[]);
+    assert_eq!(run(&fixture, &[]), 0);
+    assert_eq!(run(&fixture, &["--format", "json"]), 0);
+}
+
+#[test]
+fn test_exit_code_partial_and_any() {
+    let fixture = fixture(&[]);
+    assert_eq!(run(&fixture, &["--fail-on", "partial"]), 1);
+    assert_eq!(run(&fixture, &["--fail-on", "any"]), 1);
+    assert_eq!(run(&fixture, &["--fail-on", "any", "--fail-threshold", "1"]), 0);
+}
+
+#[test]
+fn test_exit_code_operational_error() {
+    let fixture = fixture(&[]);
+
+    // Not a git repository
+    let not_a_repo = TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-crash-audit"))
+        .current_dir(fixture.workdir.path())
+        .arg(not_a_repo.path())
+        .args(["--owner", "example", "--repo", "project"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+
+    // Unreadable issue cache
+    std::fs::write(fixture.cache_file(), "not json").unwrap();
+    assert_eq!(run(&fixture, &[]), 2);
+}
+
+#[test]
+fn test_exit_code_with_baseline() {
+    let fixture = fixture(&[100, 300]);
+    assert_eq!(run(&fixture, &["--update-baseline"]), 0);
+
+    let baseline = std::fs::read_to_string(fixture.workdir.path().join(".crash-audit-baseline.toml")).unwrap();
+    assert!(baseline.contains("issue = 100\n"));
+    assert_eq!(run(&fixture, &["--fail-on", "any"]), 0);
+
+    // Expired entries stop suppressing
+    let expired = baseline.replace("issue = 100\n", "issue = 100\nexpires = 2000-01-01\n");
+    std::fs::write(fixture.workdir.path().join(".crash-audit-baseline.toml"), expired).unwrap();
+    assert_eq!(run(&fixture, &[]), 1);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{command, fixture, output, run};
+use tempfile::TempDir;
+
+#[test]
+fn test_offline_uses_cache() {
+    let fixture = fixture(&[100, 300]);
+    assert_eq!(run(&fixture, &["--offline"]), 1);
+    assert_eq!(run(&fixture, &["--offline", "--fail-on", "partial", "--fail-threshold", "1"]), 0);
+}
+
+#[test]
+fn test_offline_without_cache() {
+    let fixture = fixture(&[]);
+    std::fs::remove_file(fixture.cache_file()).unwrap();
+
+    let output = output(&fixture, &["--offline"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No cached issues for example/project"), "{}", stderr);
+}
+
+#[test]
+fn test_issue_state_file() {
+    // The file wins over the cache, which says everything is closed
+    let fixture = fixture(&[]);
+    let states = fixture.workdir.path().join("states.csv");
+    std::fs::write(&states, "number,state\n100,open\n200,closed\n").unwrap();
+
+    let output = output(&fixture, &["--offline", "--issue-states", "states.csv", "--format", "json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["out_of_sync"][0]["issue_number"], 100);
+    assert_eq!(report["statistics"]["total_open_issues"], 1);
+
+    // 300 is not listed and counts as closed
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("no state known for 1 issue(s)"), "{}", stderr);
+}
+
+#[test]
+fn test_offline_with_stale_cache() {
+    let fixture = fixture(&[100, 300]);
+    let cache_file = fixture.cache_file();
+    let mut cache: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&cache_file).unwrap()).unwrap();
+    cache["timestamp"] = serde_json::json!(chrono::Utc::now() - chrono::Duration::days(3));
+    std::fs::write(&cache_file, cache.to_string()).unwrap();
+
+    // Stale data is still used, with a warning and a flag in the report
+    let stale = output(&fixture, &["--offline", "--max-cache-age", "2d", "--format", "json"]);
+    assert_eq!(stale.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&stale.stderr);
+    assert!(stderr.contains("WARNING: the issue cache is 3 days old (maximum 2 days)"), "{}", stderr);
+    let report: serde_json::Value = serde_json::from_slice(&stale.stdout).unwrap();
+    assert_eq!(report["metadata"]["cache_stale"], true);
+    assert_eq!(report["metadata"]["cache_max_age_seconds"], 2 * 86400);
+
+    let fresh = output(&fixture, &["--offline", "--max-cache-age", "1w", "--format", "json"]);
+    let report: serde_json::Value = serde_json::from_slice(&fresh.stdout).unwrap();
+    assert_eq!(report["metadata"]["cache_stale"], false);
+}
+
+#[test]
+fn test_cache_dir() {
+    // The cache is found from any working directory
+    let fixture = fixture(&[100, 300]);
+    let elsewhere = TempDir::new().unwrap();
+    let status = command(&fixture)
+        .current_dir(elsewhere.path())
+        .arg(fixture.repo.path())
+        .arg("--offline")
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(1));
+    assert!(fixture.xdg_cache_home().join("rust-crash-audit/scan_checkpoints.json").exists());
+
+    // --cache-dir points somewhere else entirely
+    let output = output(&fixture, &["--offline", "--cache-dir", "other"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("other/example/project/open_issues.json is missing"), "{}", stderr);
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::fixture;
+use rust_crash_audit::progress::{Progress, ProgressMessage};
+use rust_crash_audit::{Auditor, OpenIssueSource};
+use std::sync::{Arc, Mutex};
+
+#[tokio::test]
+async fn test_progress_callback() {
+    let fixture = fixture(&[100, 300]);
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let received = lines.clone();
+    let progress = Progress::new(move |message| {
+        if let ProgressMessage::Line(line) = message {
+            received.lock().unwrap().push(line.to_string());
+        }
+    });
+
+    let result = Auditor::new(fixture.repo.path())
+        .cache_dir(fixture.xdg_cache_home().join("rust-crash-audit"))
+        .issue_source(OpenIssueSource::Offline)
+        .progress(progress)
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(result.report.out_of_sync.len(), 1);
+
+    let lines = lines.lock().unwrap();
+    assert_eq!(lines[0], "Scanning example/project repository...");
+    assert!(lines.iter().any(|line| line.starts_with("Using cached data (updated")), "{:?}", lines);
+    // Details only come with verbose set
+    assert!(!lines.iter().any(|line| line == "Current crash test files:"), "{:?}", lines);
+}